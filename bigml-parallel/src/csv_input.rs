@@ -0,0 +1,57 @@
+//! Reading tasks from a CSV file.
+
+use anyhow::{format_err, Context, Result};
+use serde_json::Value;
+
+use crate::execution_input::ExecutionInput;
+use crate::ExecutionTask;
+
+/// Parse `data` as a CSV file with a header row, returning one task per row.
+/// The column named `resource_column` contains the resource to process, and
+/// every other column is passed as an input to the script. Cells are parsed as
+/// JSON if possible, or treated as strings otherwise. Empty cells are omitted.
+pub fn parse_csv_tasks(
+    data: &[u8],
+    resource_column: &str,
+) -> Result<Vec<ExecutionTask>> {
+    let mut rdr = csv::Reader::from_reader(data);
+    let headers = rdr.headers().context("could not read CSV headers")?.clone();
+    let resource_idx = headers
+        .iter()
+        .position(|h| h == resource_column)
+        .ok_or_else(|| format_err!("no column {:?} in CSV input", resource_column))?;
+
+    let mut tasks = vec![];
+    for row in rdr.records() {
+        let row = row.context("could not read CSV row")?;
+        let mut task = ExecutionTask::new(row[resource_idx].to_owned());
+        for (idx, (name, cell)) in headers.iter().zip(row.iter()).enumerate() {
+            if idx == resource_idx || cell.is_empty() {
+                continue;
+            }
+            let value = serde_json::from_str(cell)
+                .unwrap_or_else(|_| Value::String(cell.to_owned()));
+            task.inputs.push(ExecutionInput {
+                name: name.to_owned(),
+                value,
+            });
+        }
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+#[test]
+fn parses_csv_tasks() {
+    let data = b"id,n,label\ndataset/1,2,a\ndataset/2,,\"b,c\"\n";
+    let tasks = parse_csv_tasks(data, "id").unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].resource, "dataset/1");
+    assert_eq!(tasks[0].inputs[0].name, "n");
+    assert_eq!(tasks[0].inputs[0].value, Value::from(2));
+    assert_eq!(tasks[0].inputs[1].value, Value::from("a"));
+    assert_eq!(tasks[1].inputs.len(), 1);
+    assert_eq!(tasks[1].inputs[0].value, Value::from("b,c"));
+
+    assert!(parse_csv_tasks(data, "missing").is_err());
+}
@@ -0,0 +1,96 @@
+//! Downloading execution outputs to local files.
+
+use anyhow::{format_err, Context, Error, Result};
+use bigml::{
+    resource::{Dataset, Execution, Id, Resource},
+    Client,
+};
+use std::{path::PathBuf, str::FromStr};
+use tokio::{fs, io::AsyncWriteExt};
+use tracing::debug;
+
+/// An output to download after each successful execution, specified as
+/// "name=path_template".
+#[derive(Clone, Debug)]
+pub struct DownloadOutput {
+    /// The name of the output to download.
+    pub name: String,
+
+    /// Where to save it. `{resource}`, `{execution}` and `{output}` will be
+    /// replaced with the input resource, the execution and the output ID.
+    pub template: String,
+}
+
+impl FromStr for DownloadOutput {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, template) = s.split_once('=').ok_or_else(|| {
+            format_err!("download output {:?} must have form \"name=path\"", s)
+        })?;
+        Ok(DownloadOutput {
+            name: name.to_owned(),
+            template: template.to_owned(),
+        })
+    }
+}
+
+impl DownloadOutput {
+    /// The path to use when downloading `output`, created by processing
+    /// `resource` using `execution`.
+    fn path(&self, resource: &str, execution: &str, output: &str) -> PathBuf {
+        let path = self
+            .template
+            .replace("{resource}", &path_safe(resource))
+            .replace("{execution}", &path_safe(execution))
+            .replace("{output}", &path_safe(output));
+        PathBuf::from(path)
+    }
+
+    /// Download this output of `execution`, which processed `resource`.
+    pub async fn download(
+        &self,
+        client: &Client,
+        resource: &str,
+        execution: &Execution,
+    ) -> Result<()> {
+        let output = execution.execution.get::<Id<Dataset>>(&self.name)?;
+        let path = self.path(resource, execution.id().as_str(), output.as_str());
+        debug!("downloading {} to {}", output, path.display());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("could not create {}", parent.display()))?;
+        }
+
+        let mut res = client.download(&output).await?;
+        let mut file = fs::File::create(&path)
+            .await
+            .with_context(|| format!("could not create {}", path.display()))?;
+        while let Some(chunk) = res.chunk().await? {
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("could not write {}", path.display()))?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// Replace any characters in `id` which might cause trouble in a file name.
+fn path_safe(id: &str) -> String {
+    id.replace('/', "_")
+}
+
+#[test]
+fn fills_in_path_templates() {
+    let download = "dataset=out/{resource}-{output}.csv"
+        .parse::<DownloadOutput>()
+        .unwrap();
+    assert_eq!(download.name, "dataset");
+    assert_eq!(
+        download.path("source/1", "execution/2", "dataset/3"),
+        PathBuf::from("out/source_1-dataset_3.csv"),
+    );
+    assert!("dataset".parse::<DownloadOutput>().is_err());
+}
@@ -0,0 +1,114 @@
+//! Limits which protect against a run using more BigML resources than
+//! expected.
+
+use anyhow::{format_err, Error, Result};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// Limits on how much work a run may do, shared between all our tasks.
+#[derive(Debug, Default)]
+pub struct Guards {
+    /// The maximum number of executions to create, including retries.
+    max_executions: Option<usize>,
+    /// The maximum total BigML processing time of our executions.
+    max_total_elapsed_ms: Option<u64>,
+    /// The number of executions we've started.
+    executions: AtomicUsize,
+    /// The total processing time of our successful executions.
+    elapsed_ms: AtomicU64,
+    /// Has a limit prevented us from doing any work?
+    tripped: AtomicBool,
+}
+
+impl Guards {
+    /// Create a new set of guards with the specified limits.
+    pub fn new(
+        max_executions: Option<usize>,
+        max_total_elapsed_ms: Option<u64>,
+    ) -> Guards {
+        Guards {
+            max_executions,
+            max_total_elapsed_ms,
+            ..Guards::default()
+        }
+    }
+
+    /// Return an error if we have reached any of our limits.
+    pub fn check(&self) -> Result<()> {
+        match self.limit_error() {
+            Some(err) => {
+                self.tripped.store(true, Ordering::SeqCst);
+                Err(err)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record that we're about to create an execution, or return an error if
+    /// doing so would exceed our limits.
+    pub fn start_execution(&self) -> Result<()> {
+        self.check()?;
+        let max = self.max_executions;
+        self.executions
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                if max.is_none_or(|max| n < max) {
+                    Some(n + 1)
+                } else {
+                    None
+                }
+            })
+            .map(|_| ())
+            .or_else(|_| self.check())
+    }
+
+    /// Record that a successful execution used `elapsed_ms` of processing
+    /// time.
+    pub fn record_elapsed(&self, elapsed_ms: u64) {
+        self.elapsed_ms.fetch_add(elapsed_ms, Ordering::SeqCst);
+    }
+
+    /// If a limit ever prevented us from doing work, return an error
+    /// describing it.
+    pub fn tripped(&self) -> Option<Error> {
+        if self.tripped.load(Ordering::SeqCst) {
+            self.limit_error()
+        } else {
+            None
+        }
+    }
+
+    /// If we've reached one of our limits, return an error describing it.
+    fn limit_error(&self) -> Option<Error> {
+        let executions = self.executions.load(Ordering::SeqCst);
+        let elapsed_ms = self.elapsed_ms.load(Ordering::SeqCst);
+        match (self.max_executions, self.max_total_elapsed_ms) {
+            (Some(max), _) if executions >= max => Some(format_err!(
+                "stopped after reaching --max-executions {}",
+                max
+            )),
+            (_, Some(max)) if elapsed_ms >= max => Some(format_err!(
+                "stopped after reaching --max-total-elapsed-ms {} ({} ms used)",
+                max,
+                elapsed_ms
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn guards_enforce_limits() {
+    let guards = Guards::new(Some(2), None);
+    assert!(guards.start_execution().is_ok());
+    assert!(guards.start_execution().is_ok());
+    assert!(guards.tripped().is_none());
+    assert!(guards.start_execution().is_err());
+    assert!(guards.tripped().is_some());
+
+    let guards = Guards::new(None, Some(1000));
+    assert!(guards.start_execution().is_ok());
+    guards.record_elapsed(600);
+    assert!(guards.check().is_ok());
+    guards.record_elapsed(600);
+    assert!(guards.check().is_err());
+    assert!(guards.tripped().is_some());
+}
@@ -1,31 +1,52 @@
 //! A CLI tool for executing BigML jobs in parallel.
 
-use anyhow::{Error, Result};
+use anyhow::{format_err, Context, Error, Result};
 use bigml::{
     self,
-    resource::{execution, Execution, Id, Resource, Script},
+    resource::{
+        execution, script, Dataset, Execution, Id, Resource, ResourceType, Script,
+    },
     try_wait, try_with_permanent_failure,
     wait::{wait, BackoffType, WaitOptions, WaitStatus},
-    Client,
+    Client, ClientBuilder,
 };
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use futures::{self, stream, FutureExt, StreamExt, TryStreamExt};
 use regex::Regex;
-use std::{process, sync::Arc, time::Duration};
-use tokio::io;
-use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
-use tracing::{debug, error, instrument};
+use std::{
+    future,
+    num::NonZeroUsize,
+    path::PathBuf,
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::io::{self, AsyncReadExt};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tracing::{debug, error, field, field::display, instrument, warn, Span};
 use tracing_subscriber::{
     fmt::{format::FmtSpan, Subscriber},
     prelude::*,
     EnvFilter,
 };
 
+mod csv_input;
+mod download_output;
 mod execution_input;
+mod guards;
 mod line_delimited_json_codec;
+mod output;
+mod previous_output;
+mod script_file;
+mod tagged_resources;
 
+use download_output::DownloadOutput;
 use execution_input::ExecutionInput;
-use line_delimited_json_codec::LineDelimitedJsonCodec;
+use guards::Guards;
+use output::{ErrorRecord, Output, OutputFormat, OutputRecord};
 
 /// Our standard stream type, containing values of type `T`.
 type BoxStream<T> = futures::stream::BoxStream<'static, Result<T>>;
@@ -33,6 +54,62 @@ type BoxStream<T> = futures::stream::BoxStream<'static, Result<T>>;
 /// Our standard future type, yield a value of type `T`.
 type BoxFuture<T> = futures::future::BoxFuture<'static, Result<T>>;
 
+/// A resource to process, plus any inputs specific to that resource.
+#[derive(Clone, Debug)]
+pub struct ExecutionTask {
+    /// The resource to process.
+    pub resource: String,
+    /// Extra inputs for this resource, which override any `--input` values
+    /// with the same name.
+    pub inputs: Vec<ExecutionInput>,
+}
+
+impl ExecutionTask {
+    /// Create a new task with no extra inputs.
+    pub fn new(resource: String) -> ExecutionTask {
+        ExecutionTask {
+            resource,
+            inputs: vec![],
+        }
+    }
+}
+
+/// Formats we can read from standard input.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum InputFormat {
+    /// One resource ID per line.
+    Lines,
+    /// A CSV file with a header row.
+    Csv,
+}
+
+/// Formats we can use for log output.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum LogFormat {
+    /// Human-readable text.
+    Text,
+    /// One JSON object per line, for use with log aggregation tools.
+    Json,
+}
+
+/// How we back off between retries.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Backoff {
+    /// Double the retry interval after each failure.
+    Exponential,
+    /// Use the same retry interval every time.
+    Linear,
+}
+
+impl From<Backoff> for BackoffType {
+    fn from(backoff: Backoff) -> BackoffType {
+        match backoff {
+            Backoff::Exponential => BackoffType::Exponential,
+            Backoff::Linear => BackoffType::Linear,
+        }
+    }
+}
+
 /// Our command-line arguments.
 #[derive(Debug, Parser)]
 #[command(
@@ -43,8 +120,19 @@ type BoxFuture<T> = futures::future::BoxFuture<'static, Result<T>>;
 )]
 struct Opt {
     /// The WhizzML script ID to run.
-    #[arg(long = "script", short = 's')]
-    script: Id<Script>,
+    #[arg(
+        long = "script",
+        short = 's',
+        required_unless_present = "script_file",
+        conflicts_with = "script_file"
+    )]
+    script: Option<Id<Script>>,
+
+    /// A local WhizzML file to upload as a new script before running it. The
+    /// script's inputs and outputs will be declared based on --input,
+    /// --resource-input-name, --output and --output-type.
+    #[arg(long = "script-file", value_name = "PATH")]
+    script_file: Option<PathBuf>,
 
     /// The name to use for our execution objects.
     #[arg(long = "name", short = 'n')]
@@ -55,6 +143,32 @@ struct Opt {
     #[arg(long = "resource", short = 'r')]
     resources: Vec<String>,
 
+    /// Process all resources with this tag, instead of reading resource IDs
+    /// from standard input. Requires --resource-type.
+    #[arg(
+        long = "resources-by-tag",
+        value_name = "TAG",
+        requires = "resource_type",
+        conflicts_with = "resources"
+    )]
+    resources_by_tag: Option<String>,
+
+    /// The type of resource to look for when using --resources-by-tag, such
+    /// as "dataset". We support batchcentroid, batchprediction, cluster,
+    /// dataset, ensemble, execution and source.
+    #[arg(long = "resource-type")]
+    resource_type: Option<ResourceType>,
+
+    /// The format of standard input. When using "csv", the resource ID is
+    /// read from --resource-column, and every other column is passed as an
+    /// input to our script.
+    #[arg(long = "input-format", value_enum, default_value = "lines")]
+    input_format: InputFormat,
+
+    /// The CSV column containing the resource IDs to process.
+    #[arg(long = "resource-column", default_value = "resource")]
+    resource_column: String,
+
     /// The input name used to pass the dataset.
     #[arg(long = "resource-input-name", short = 'R', default_value = "resource")]
     resource_input_name: String,
@@ -68,6 +182,11 @@ struct Opt {
     #[arg(long = "output", short = 'o')]
     outputs: Vec<String>,
 
+    /// The type of an output, specified as "name=type", used when creating a
+    /// script with --script-file. Outputs default to "resource-id".
+    #[arg(long = "output-type", value_parser = script_file::parse_output_type)]
+    output_types: Vec<(String, script::Type)>,
+
     /// How many BigML tasks should we use at a time?
     #[arg(long = "max-tasks", short = 'J', default_value = "2")]
     max_tasks: usize,
@@ -83,23 +202,155 @@ struct Opt {
     /// How many times should we retry a failed execution matching --retry-on?
     #[arg(long = "retry-count", default_value = "0")]
     retry_count: u16,
+
+    /// How many seconds to wait before retrying. Defaults to 60 seconds
+    /// when creating an execution, and 2 minutes when re-running a failed
+    /// execution.
+    #[arg(long = "retry-interval", value_name = "SECS")]
+    retry_interval: Option<u64>,
+
+    /// How to increase --retry-interval after each failure.
+    #[arg(long = "backoff", value_enum, default_value = "exponential")]
+    backoff: Backoff,
+
+    /// How many temporary errors to allow while creating each execution,
+    /// typically caused by running out of BigML task slots.
+    #[arg(long = "allowed-errors", value_name = "N", default_value = "6")]
+    allowed_errors: u16,
+
+    /// The output of a previous run. Any resources which were successfully
+    /// processed by that run will be skipped.
+    #[arg(long = "skip-from", value_name = "FILE")]
+    skip_from: Option<PathBuf>,
+
+    /// The maximum number of BigML API requests to make per second, across
+    /// all tasks. This includes both creating executions and polling them.
+    #[arg(long = "max-requests-per-second", value_parser = parse_rate)]
+    max_requests_per_second: Option<f64>,
+
+    /// The maximum number of BigML API requests to have in flight at once,
+    /// across all tasks.
+    #[arg(
+        long = "max-concurrent-requests",
+        value_name = "N",
+        value_parser = parse_request_count
+    )]
+    max_concurrent_requests: Option<usize>,
+
+    /// Download an output dataset as CSV after each successful execution,
+    /// specified as "name=path". In the path, "{resource}", "{execution}"
+    /// and "{output}" will be replaced with the corresponding IDs.
+    #[arg(long = "download-output", value_name = "NAME=PATH")]
+    download_outputs: Vec<DownloadOutput>,
+
+    /// Delete each execution once it has finished successfully. The
+    /// execution will still be printed.
+    #[arg(long = "delete-after-success")]
+    delete_after_success: bool,
+
+    /// Delete the dataset output with the specified name once each execution
+    /// has finished successfully.
+    #[arg(long = "delete-outputs", value_name = "NAME")]
+    delete_outputs: Vec<String>,
+
+    /// Abort all running executions as soon as any execution fails. By
+    /// default, we stop starting new executions after a failure, but wait for
+    /// running executions to finish.
+    #[arg(long = "fail-fast", conflicts_with = "keep_going")]
+    fail_fast: bool,
+
+    /// Keep going after an execution fails, writing a JSON error record with
+    /// the resource ID and error message instead of the execution. We still
+    /// exit with an error once all resources have been processed.
+    #[arg(long = "keep-going")]
+    keep_going: bool,
+
+    /// The format to use for standard output. "csv" writes one row per input
+    /// resource, with one column for each --output.
+    #[arg(long = "output-format", value_enum, default_value = "json")]
+    output_format: OutputFormat,
+
+    /// Write one pretty-printed JSON file per input resource to this
+    /// directory, in addition to standard output.
+    #[arg(long = "output-dir", value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// Don't write executions to standard output. Use with --output-dir.
+    #[arg(long = "no-stdout", requires = "output_dir")]
+    no_stdout: bool,
+
+    /// Before creating each execution, wait until BigML is running fewer
+    /// than this many executions for the account. This avoids most
+    /// "payment required" errors when other jobs share the account.
+    #[arg(long = "max-account-tasks", value_name = "N")]
+    max_account_tasks: Option<NonZeroUsize>,
+
+    /// Stop starting new executions once this many have been created,
+    /// including retries.
+    #[arg(long = "max-executions", value_name = "N")]
+    max_executions: Option<usize>,
+
+    /// Stop starting new executions once the executions we've run have used
+    /// this many milliseconds of BigML processing time, in total.
+    #[arg(long = "max-total-elapsed-ms", value_name = "MS")]
+    max_total_elapsed_ms: Option<u64>,
+
+    /// The format to use for log messages written to standard error.
+    #[arg(long = "log-format", value_enum, default_value = "text")]
+    log_format: LogFormat,
+}
+
+impl Opt {
+    /// Build `WaitOptions` from our retry arguments, using `default_interval`
+    /// unless the user specified --retry-interval.
+    fn wait_options(&self, default_interval: Duration) -> WaitOptions {
+        let interval = self
+            .retry_interval
+            .map(Duration::from_secs)
+            .unwrap_or(default_interval);
+        WaitOptions::default()
+            .retry_interval(interval)
+            .backoff_type(self.backoff.into())
+    }
+}
+
+/// Parse a positive rate.
+fn parse_rate(s: &str) -> Result<f64> {
+    let rate = s.parse::<f64>()?;
+    if rate.is_finite() && rate > 0.0 {
+        Ok(rate)
+    } else {
+        Err(format_err!("rate must be a positive number"))
+    }
+}
+
+/// Parse a positive number of requests.
+fn parse_request_count(s: &str) -> Result<usize> {
+    match s.parse::<usize>()? {
+        0 => Err(format_err!("request count must be at least 1")),
+        count => Ok(count),
+    }
 }
 
 /// A `main` function that prints out pretty errors. All the real work is done
 /// in `run`
 #[tokio::main]
 async fn main() {
+    let opt = Opt::parse();
+
     // Configure tracing.
     let filter = EnvFilter::from_default_env();
-    Subscriber::builder()
+    let builder = Subscriber::builder()
         .with_writer(std::io::stderr)
         .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-        .with_env_filter(filter)
-        .finish()
-        .init();
+        .with_env_filter(filter);
+    match opt.log_format {
+        LogFormat::Text => builder.finish().init(),
+        LogFormat::Json => builder.json().finish().init(),
+    }
 
     // Run our main program and watch for errors.
-    if let Err(err) = run().await {
+    if let Err(err) = run(opt).await {
         eprintln!("Error: {}", err);
         for cause in err.chain().skip(1) {
             eprintln!("  caused by: {}", cause);
@@ -110,33 +361,110 @@ async fn main() {
 }
 
 /// Our real `main` function, called by `main`.
-#[instrument(level = "trace", name = "bigml_parallel")]
-async fn run() -> Result<()> {
-    let opt = Opt::parse();
+#[instrument(level = "trace", name = "bigml_parallel", skip(opt))]
+async fn run(mut opt: Opt) -> Result<()> {
     debug!("command-line options: {:?}", opt);
 
+    // Create a single client, shared by all our tasks, so that they all share
+    // the same rate and concurrency limits.
+    let client = ClientBuilder::from_env()?
+        .max_requests_per_second(opt.max_requests_per_second)
+        .max_concurrent_requests(opt.max_concurrent_requests)
+        .build()?;
+
     // We want to represent our input resource IDs as an asynchronous stream,
-    // which will make it very easy to have controlled parallel execution.
-    let resources: BoxStream<String> = if !opt.resources.is_empty() {
+    // which will make it very easy to have controlled parallel execution. We
+    // also keep an example of each per-resource input, in case we need to
+    // declare it.
+    let mut sample_inputs: Vec<ExecutionInput> = vec![];
+    let tasks: BoxStream<ExecutionTask> = if !opt.resources.is_empty() {
         // Turn our `--resource` arguments into a stream.
         let resources = opt.resources.clone();
-        stream::iter(resources.into_iter().map(Ok)).boxed()
+        stream::iter(resources.into_iter().map(|r| Ok(ExecutionTask::new(r)))).boxed()
+    } else if let (Some(tag), Some(resource_type)) =
+        (&opt.resources_by_tag, opt.resource_type)
+    {
+        // Look up our resources on BigML.
+        let resources =
+            tagged_resources::tagged_resource_ids(&client, resource_type, tag).await?;
+        debug!("found {} resources tagged {:?}", resources.len(), tag);
+        stream::iter(resources.into_iter().map(|r| Ok(ExecutionTask::new(r)))).boxed()
     } else {
-        // Parse standard input as a stream of dataset IDs.
-        let lines = FramedRead::new(io::stdin(), LinesCodec::new());
-        lines.map_err(|e| -> Error { e.into() }).boxed()
+        match opt.input_format {
+            InputFormat::Lines => {
+                // Parse standard input as a stream of dataset IDs.
+                let lines = FramedRead::new(io::stdin(), LinesCodec::new());
+                lines
+                    .map_ok(ExecutionTask::new)
+                    .map_err(|e| -> Error { e.into() })
+                    .boxed()
+            }
+            InputFormat::Csv => {
+                // CSV files are small compared to the work we'll do on
+                // each row, so just read the whole thing.
+                let mut data = vec![];
+                io::stdin().read_to_end(&mut data).await?;
+                let tasks = csv_input::parse_csv_tasks(&data, &opt.resource_column)?;
+                for input in tasks.iter().flat_map(|task| &task.inputs) {
+                    if !sample_inputs.iter().any(|i| i.name == input.name) {
+                        sample_inputs.push(input.clone());
+                    }
+                }
+                stream::iter(tasks.into_iter().map(Ok)).boxed()
+            }
+        }
     };
 
+    // Skip any resources which were already processed by a previous run.
+    let tasks = if let Some(skip_from) = &opt.skip_from {
+        let skip =
+            previous_output::processed_resources(skip_from, &opt.resource_input_name)
+                .await?;
+        debug!("skipping {} previously processed resources", skip.len());
+        tasks
+            .try_filter(move |task| future::ready(!skip.contains(&task.resource)))
+            .boxed()
+    } else {
+        tasks
+    };
+
+    // Upload our script, if we were given a local file.
+    if let Some(path) = &opt.script_file {
+        let script =
+            script_file::create_script(&client, &opt, path, &sample_inputs).await?;
+        opt.script = Some(script);
+    }
+
     // Wrap our command line arguments in a thread-safe reference counter, so
     // that all our parallel tasks can access them.
     let opt = Arc::new(opt);
 
-    // Transform our stream of IDs into a stream of _futures_, each of which will
-    // return an `Execution` object from BigML.
+    // Once this is set, or one of our guards is exhausted, we stop starting
+    // new executions.
+    let stopped = Arc::new(AtomicBool::new(false));
+    let stopped2 = stopped.clone();
+    let guards = Arc::new(Guards::new(opt.max_executions, opt.max_total_elapsed_ms));
+    let guards2 = guards.clone();
+    let tasks = tasks.take_while(move |_| {
+        future::ready(!stopped2.load(Ordering::SeqCst) && guards2.check().is_ok())
+    });
+
+    // Transform our stream of tasks into a stream of _futures_, each of which will
+    // return an `Execution` object from BigML, along with the ID we started
+    // with.
     let opt2 = opt.clone();
-    let execution_futures: BoxStream<BoxFuture<Execution>> = resources
-        .map_ok(move |resource| {
-            resource_id_to_execution(opt2.clone(), resource).boxed()
+    let guards3 = guards.clone();
+    let execution_futures: BoxStream<BoxFuture<(String, Result<Execution>)>> = tasks
+        .map_ok(move |task| {
+            let opt = opt2.clone();
+            let guards = guards3.clone();
+            let client = client.clone();
+            async move {
+                let resource = task.resource.clone();
+                let result = resource_id_to_execution(opt, guards, client, task).await;
+                Ok((resource, result))
+            }
+            .boxed()
         })
         .boxed();
 
@@ -148,29 +476,82 @@ async fn run() -> Result<()> {
     // TODO: In tokio 0.1, this had weird buffering behavior, and
     // appeared to wait until it buffered `opt.max_tasks` items. I have
     // not verified this in tokio 0.2.
-    let executions: BoxStream<Execution> = execution_futures
+    let mut executions: BoxStream<(String, Result<Execution>)> = execution_futures
         .try_buffer_unordered(opt.max_tasks)
         .boxed();
 
-    // Copy our stream of `Execution`s to standard output as line-delimited
-    // JSON.
-    //
-    // TODO: `forward` may also have weird buffering behavior.
-    let stdout = FramedWrite::new(io::stdout(), LineDelimitedJsonCodec::new());
-    executions.forward(stdout).await?;
-    Ok(())
+    // Copy our stream of `Execution`s to our output, handling failures as
+    // specified by our options.
+    if matches!(opt.output_format, OutputFormat::Csv) && opt.outputs.is_empty() {
+        return Err(format_err!(
+            "--output-format csv requires at least one --output"
+        ));
+    }
+    let mut output = Output::new(
+        opt.output_format,
+        &opt.outputs,
+        !opt.no_stdout,
+        opt.output_dir.as_deref(),
+    )
+    .await?;
+    let mut first_failure = None;
+    let mut failure_count = 0;
+    while let Some((resource, result)) = executions.try_next().await? {
+        let err = match result {
+            Ok(execution) => {
+                output
+                    .write(&resource, OutputRecord::Execution(Box::new(execution)))
+                    .await?;
+                continue;
+            }
+            Err(err) => err,
+        };
+        if opt.fail_fast {
+            // Dropping `executions` will abort everything still running.
+            return Err(err.context(format!("could not process {}", resource)));
+        }
+        error!("could not process {}: {:#}", resource, err);
+        failure_count += 1;
+        if opt.keep_going {
+            let record = OutputRecord::Error(ErrorRecord::new(resource.clone(), &err));
+            output.write(&resource, record).await?;
+        } else {
+            stopped.store(true, Ordering::SeqCst);
+            if first_failure.is_none() {
+                first_failure =
+                    Some(err.context(format!("could not process {}", resource)));
+            }
+        }
+    }
+    match first_failure {
+        Some(err) => Err(err),
+        None if failure_count > 0 => Err(format_err!(
+            "{} resources could not be processed",
+            failure_count
+        )),
+        None => match guards.tripped() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        },
+    }
 }
 
-/// Use our command-line options and a resource ID to create and run a BigML
+/// Use our command-line options and a task to create and run a BigML
 /// execution.
-#[instrument(level = "debug", fields(script = %opt.script), skip(opt))]
+#[instrument(
+    level = "debug",
+    fields(script = opt.script.as_ref().map(Id::as_str), resource = %task.resource),
+    skip(opt, guards, client, task)
+)]
 async fn resource_id_to_execution(
     opt: Arc<Opt>,
-    resource: String,
+    guards: Arc<Guards>,
+    client: Client,
+    task: ExecutionTask,
 ) -> Result<Execution> {
     // Specify what script to run.
     let mut args = execution::Args::default();
-    args.script = Some(opt.script.clone());
+    args.script = opt.script.clone();
 
     // Optionally set the script name.
     if let Some(name) = opt.name.as_ref() {
@@ -178,10 +559,16 @@ async fn resource_id_to_execution(
     }
 
     // Specify the input dataset.
-    args.add_input(&opt.resource_input_name, &resource)?;
+    args.add_input(&opt.resource_input_name, &task.resource)?;
 
-    // Add any other inputs.
+    // Add any other inputs, letting inputs from our task override those
+    // specified on the command line.
     for input in &opt.inputs {
+        if !task.inputs.iter().any(|i| i.name == input.name) {
+            args.add_input(&input.name, &input.value)?;
+        }
+    }
+    for input in &task.inputs {
         args.add_input(&input.name, &input.value)?;
     }
 
@@ -201,40 +588,109 @@ async fn resource_id_to_execution(
     //     a. Retry the creation if that fails with a transient error. This is often
     //        caused by running out of slots.
     //     b. Internally retry the `wait` if it fails with a transient network error.
-    let exec_wait_opt = WaitOptions::default()
-        .retry_interval(Duration::from_secs(2 * 60))
-        .backoff_type(BackoffType::Exponential)
+    let exec_wait_opt = opt
+        .wait_options(Duration::from_secs(2 * 60))
         .allowed_errors(opt.retry_count);
-    let execution = wait(&exec_wait_opt, || {
-        create_and_wait_execution(&args, opt.retry_on.as_ref())
+    let create_wait_opt = opt
+        .wait_options(Duration::from_secs(60))
+        .allowed_errors(opt.allowed_errors);
+    let execution = wait::<_, Error, _, _>(&exec_wait_opt, || async {
+        // Check our guards before each attempt, because retries cost as much
+        // as the original execution.
+        if let Err(err) = guards.start_execution() {
+            return WaitStatus::FailedPermanently(err);
+        }
+        match create_and_wait_execution(
+            &client,
+            &args,
+            &create_wait_opt,
+            opt.max_account_tasks,
+            opt.retry_on.as_ref(),
+        )
+        .await
+        {
+            WaitStatus::Finished(execution) => {
+                guards.record_elapsed(execution.status.elapsed.unwrap_or(0));
+                WaitStatus::Finished(execution)
+            }
+            WaitStatus::Waiting => WaitStatus::Waiting,
+            WaitStatus::FailedTemporarily(err) => {
+                WaitStatus::FailedTemporarily(err.into())
+            }
+            WaitStatus::FailedPermanently(err) => {
+                WaitStatus::FailedPermanently(err.into())
+            }
+        }
     })
     .await?;
+
+    for download in &opt.download_outputs {
+        download
+            .download(&client, &task.resource, &execution)
+            .await
+            .with_context(|| format!("could not download output {}", download.name))?;
+    }
+
+    clean_up_execution(&opt, &client, &execution).await;
     Ok(execution)
 }
 
+/// Delete any resources which our command-line options say we don't need to
+/// keep after a successful execution. Failures to delete are logged, but
+/// don't cause the execution to fail.
+#[instrument(level = "debug", skip(opt, client, execution), fields(execution = %execution.id()))]
+async fn clean_up_execution(opt: &Opt, client: &Client, execution: &Execution) {
+    for name in &opt.delete_outputs {
+        let dataset = match execution.execution.get::<Id<Dataset>>(name) {
+            Ok(dataset) => dataset,
+            Err(err) => {
+                warn!("could not find output {} to delete: {}", name, err);
+                continue;
+            }
+        };
+        if let Err(err) = client.delete(&dataset).await {
+            warn!("could not delete output {} ({}): {}", name, dataset, err);
+        }
+    }
+    if opt.delete_after_success {
+        if let Err(err) = client.delete(execution.id()).await {
+            warn!("could not delete {}: {}", execution.id(), err);
+        }
+    }
+}
+
 /// Create a BigML execution and wait for it to finish.
 ///
 /// Returns a `WaitStatus`, allowing our caller to retry us as necessary.
-#[instrument(level = "trace")]
+#[instrument(
+    level = "debug",
+    skip(client, args, create_wait_opt),
+    fields(execution = field::Empty)
+)]
 async fn create_and_wait_execution(
+    client: &Client,
     args: &execution::Args,
+    create_wait_opt: &WaitOptions,
+    max_account_tasks: Option<NonZeroUsize>,
     retry_on: Option<&Regex>,
 ) -> WaitStatus<Execution, bigml::Error> {
-    // If we can't create a client, just give up immediately.
-    let client = try_with_permanent_failure!(Client::new_from_env());
-
-    // Attempt to create a new execution. This has custom retry logic with
-    // unusually long timeouts because temporary failures here are generally
-    // caused by hitting API limits, and if we wait 30 minutes, somebody else's
-    // batch job may finish. But if those retries fail, we want to fail
-    // permanently.
-    let create_wait_opt = WaitOptions::default()
-        .retry_interval(Duration::from_secs(60))
-        .backoff_type(BackoffType::Exponential)
-        .allowed_errors(6);
+    // Attempt to create a new execution. This uses `create_wait_opt`, which
+    // has unusually long timeouts by default because temporary failures here
+    // are generally caused by hitting API limits, and if we wait 30 minutes,
+    // somebody else's batch job may finish. But if those retries fail, we
+    // want to fail permanently.
     let execution = try_with_permanent_failure!(
-        wait(&create_wait_opt, || {
+        wait(create_wait_opt, || {
             async {
+                // Give other jobs a chance to finish before we ask for another
+                // task slot.
+                if let Some(max) = max_account_tasks {
+                    try_wait!(
+                        client
+                            .await_task_slot_opt::<Execution>(max, create_wait_opt)
+                            .await
+                    );
+                }
                 // We use `try_wait`, because it knows which errors are
                 // permanent and which are temporary.
                 WaitStatus::Finished(try_wait!(client.create(args).await))
@@ -243,6 +699,8 @@ async fn create_and_wait_execution(
         .await
     );
 
+    Span::current().record("execution", display(execution.id()));
+
     // `client.wait` has its own internal retry logic, but it only triggers for
     // things like failed HTTP calls to BigML. We also want to retry any script
     // errors that match `retry_on`.
@@ -0,0 +1,217 @@
+//! The records we write to standard output.
+
+use anyhow::{Context, Error, Result};
+use bigml::resource::{Execution, Resource};
+use clap::ValueEnum;
+use futures::SinkExt;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::{
+    fs,
+    io::{self, AsyncWriteExt},
+};
+use tokio_util::codec::FramedWrite;
+
+use crate::line_delimited_json_codec::LineDelimitedJsonCodec;
+
+/// A single line of our output.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum OutputRecord {
+    /// An execution which finished successfully.
+    Execution(Box<Execution>),
+    /// A resource which we could not process.
+    Error(ErrorRecord),
+}
+
+/// Information about a resource which we could not process. This is only
+/// output when running with `--keep-going`.
+#[derive(Debug, Serialize)]
+pub struct ErrorRecord {
+    /// The resource we were trying to process.
+    pub resource: String,
+    /// The error that occurred.
+    pub error: String,
+    /// The errors which caused `error`, from outermost to innermost.
+    pub causes: Vec<String>,
+}
+
+impl ErrorRecord {
+    /// Describe an `error` which occurred while processing `resource`.
+    pub fn new(resource: String, error: &Error) -> ErrorRecord {
+        ErrorRecord {
+            resource,
+            error: error.to_string(),
+            causes: error
+                .chain()
+                .skip(1)
+                .map(|cause| cause.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Formats we can write to standard output.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    /// One JSON object per line.
+    Json,
+    /// A CSV file with one row per input resource, and one column per output.
+    Csv,
+}
+
+/// How we write to standard output.
+enum Stdout {
+    /// Write line-delimited JSON.
+    Json(FramedWrite<io::Stdout, LineDelimitedJsonCodec<OutputRecord>>),
+    /// Write CSV with the specified output columns.
+    Csv {
+        stdout: io::Stdout,
+        columns: Vec<String>,
+    },
+}
+
+/// Where we write our `OutputRecord`s.
+pub struct Output {
+    /// Standard output, if we're writing to it.
+    stdout: Option<Stdout>,
+    /// A directory in which to write one file per resource.
+    dir: Option<PathBuf>,
+}
+
+impl Output {
+    /// Create a new `Output`, writing to standard output using `format` if
+    /// `stdout` is true, and pretty-printed JSON files to `dir` if present.
+    /// When writing CSV, `columns` lists the execution outputs to include.
+    pub async fn new(
+        format: OutputFormat,
+        columns: &[String],
+        stdout: bool,
+        dir: Option<&Path>,
+    ) -> Result<Output> {
+        if let Some(dir) = dir {
+            fs::create_dir_all(dir)
+                .await
+                .with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        let stdout = match (stdout, format) {
+            (false, _) => None,
+            (true, OutputFormat::Json) => Some(Stdout::Json(FramedWrite::new(
+                io::stdout(),
+                LineDelimitedJsonCodec::new(),
+            ))),
+            (true, OutputFormat::Csv) => {
+                let mut stdout = io::stdout();
+                let mut header = vec!["resource".to_owned(), "execution".to_owned()];
+                header.extend(columns.iter().cloned());
+                header.push("error".to_owned());
+                stdout.write_all(&csv_line(&header)?).await?;
+                Some(Stdout::Csv {
+                    stdout,
+                    columns: columns.to_owned(),
+                })
+            }
+        };
+        Ok(Output {
+            stdout,
+            dir: dir.map(|dir| dir.to_owned()),
+        })
+    }
+
+    /// Write `record`, which describes what happened to `resource`.
+    pub async fn write(&mut self, resource: &str, record: OutputRecord) -> Result<()> {
+        if let Some(dir) = &self.dir {
+            let path = dir.join(output_file_name(resource));
+            let json = serde_json::to_vec_pretty(&record)?;
+            fs::write(&path, json)
+                .await
+                .with_context(|| format!("could not write {}", path.display()))?;
+        }
+        match &mut self.stdout {
+            None => {}
+            Some(Stdout::Json(stdout)) => stdout.send(record).await?,
+            Some(Stdout::Csv { stdout, columns }) => {
+                let row = csv_row(resource, &record, columns);
+                stdout.write_all(&csv_line(&row)?).await?;
+                stdout.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Flatten `record` into a CSV row, including the values of the execution
+/// outputs named in `columns`.
+fn csv_row(resource: &str, record: &OutputRecord, columns: &[String]) -> Vec<String> {
+    let mut row = vec![resource.to_owned()];
+    match record {
+        OutputRecord::Execution(execution) => {
+            row.push(execution.id().to_string());
+            for column in columns {
+                let value = execution
+                    .execution
+                    .outputs
+                    .iter()
+                    .find(|output| &output.name == column)
+                    .and_then(|output| output.value.as_ref());
+                row.push(match value {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.to_owned(),
+                    Some(other) => other.to_string(),
+                });
+            }
+            row.push(String::new());
+        }
+        OutputRecord::Error(err) => {
+            row.push(String::new());
+            row.extend(columns.iter().map(|_| String::new()));
+            row.push(err.error.clone());
+        }
+    }
+    row
+}
+
+/// Format `fields` as a single line of CSV.
+fn csv_line(fields: &[String]) -> Result<Vec<u8>> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record(fields)?;
+    wtr.into_inner().context("could not format CSV")
+}
+
+/// The name of the file we use to store the output for `resource`.
+fn output_file_name(resource: &str) -> String {
+    let safe = resource
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("{}.json", safe)
+}
+
+#[test]
+fn output_file_names_are_safe() {
+    assert_eq!(
+        output_file_name("dataset/5e5e5e5e5e5e5e5e5e5e5e5e"),
+        "dataset_5e5e5e5e5e5e5e5e5e5e5e5e.json",
+    );
+    assert_eq!(output_file_name("../etc"), "___etc.json");
+}
+
+#[test]
+fn error_records_become_csv_rows() {
+    let record = OutputRecord::Error(ErrorRecord {
+        resource: "dataset/1".to_owned(),
+        error: "bad, very bad".to_owned(),
+        causes: vec![],
+    });
+    let columns = vec!["n".to_owned()];
+    let row = csv_row("dataset/1", &record, &columns);
+    assert_eq!(row, &["dataset/1", "", "", "bad, very bad"]);
+    assert_eq!(csv_line(&row).unwrap(), b"dataset/1,,,\"bad, very bad\"\n");
+}
@@ -0,0 +1,77 @@
+//! Reading the output of a previous `bigml-parallel` run.
+
+use anyhow::{Context, Result};
+use bigml::resource::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashSet, path::Path};
+use tokio::fs;
+use tracing::debug;
+
+/// Just enough of an `Execution` to tell whether it succeeded, and which
+/// resource it processed.
+#[derive(Deserialize)]
+struct PreviousExecution {
+    status: PreviousStatus,
+    #[serde(default)]
+    inputs: Vec<(String, Value)>,
+}
+
+/// Just enough of an `ExecutionStatus` to tell whether it succeeded.
+#[derive(Deserialize)]
+struct PreviousStatus {
+    code: StatusCode,
+}
+
+/// Read the line-delimited JSON file at `path`, and return the value of the
+/// input `input_name` for every successful execution.
+pub async fn processed_resources(
+    path: &Path,
+    input_name: &str,
+) -> Result<HashSet<String>> {
+    let text = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("could not read {}", path.display()))?;
+    Ok(parse_processed_resources(&text, input_name))
+}
+
+/// Parse `text` as line-delimited JSON, and return the value of the input
+/// `input_name` for every successful execution. Lines which aren't
+/// executions are ignored.
+fn parse_processed_resources(text: &str, input_name: &str) -> HashSet<String> {
+    let mut resources = HashSet::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let execution = match serde_json::from_str::<PreviousExecution>(line) {
+            Ok(execution) => execution,
+            Err(err) => {
+                debug!("skipping line of previous output: {}", err);
+                continue;
+            }
+        };
+        if !execution.status.code.is_ready() {
+            continue;
+        }
+        for (name, value) in execution.inputs {
+            match value {
+                Value::String(resource) if name == input_name => {
+                    resources.insert(resource);
+                }
+                _ => {}
+            }
+        }
+    }
+    resources
+}
+
+#[test]
+fn finds_successfully_processed_resources() {
+    let text = r#"
+{"status":{"code":5},"inputs":[["resource","dataset/1"],["n",2]]}
+{"status":{"code":-1},"inputs":[["resource","dataset/2"]]}
+{"error":"something went wrong"}
+{"status":{"code":5},"inputs":[["other","dataset/3"]]}
+"#;
+    let resources = parse_processed_resources(text, "resource");
+    assert_eq!(resources.len(), 1);
+    assert!(resources.contains("dataset/1"));
+}
@@ -0,0 +1,98 @@
+//! Creating a script from a local WhizzML file.
+
+use anyhow::{format_err, Context, Result};
+use bigml::{
+    resource::{
+        script::{self, Type},
+        Id, Resource, Script,
+    },
+    Client,
+};
+use serde_json::Value;
+use std::{collections::HashSet, path::Path};
+use tracing::debug;
+
+use crate::{execution_input::ExecutionInput, Opt};
+
+/// Upload the WhizzML source code in `path` as a new script, declaring inputs
+/// and outputs based on our command-line options. `sample_inputs` contains
+/// examples of any per-resource inputs, which are used to guess their types.
+pub async fn create_script(
+    client: &Client,
+    opt: &Opt,
+    path: &Path,
+    sample_inputs: &[ExecutionInput],
+) -> Result<Id<Script>> {
+    let mut builder = script::ArgsBuilder::with_source_file(path)?;
+    let name = match &opt.name {
+        Some(name) => name.to_owned(),
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "bigml-parallel".to_owned()),
+    };
+    builder = builder.name(name);
+    for tag in &opt.tags {
+        builder = builder.tag(tag);
+    }
+
+    // Declare our inputs.
+    let mut declared = HashSet::new();
+    declared.insert(opt.resource_input_name.clone());
+    builder = builder.input(&opt.resource_input_name, Type::ResourceId);
+    for input in opt.inputs.iter().chain(sample_inputs) {
+        if declared.insert(input.name.clone()) {
+            builder = builder.input(&input.name, value_type(&input.value));
+        }
+    }
+
+    // Declare our outputs.
+    for output in &opt.outputs {
+        let type_ = opt
+            .output_types
+            .iter()
+            .find(|(name, _)| name == output)
+            .map(|(_, type_)| *type_)
+            .unwrap_or(Type::ResourceId);
+        builder = builder.output(output, type_);
+    }
+
+    let script = client
+        .create_and_wait(&builder.build())
+        .await
+        .with_context(|| format!("could not create script from {}", path.display()))?;
+    debug!("created {} from {}", script.id(), path.display());
+    Ok(script.id().to_owned())
+}
+
+/// Guess the WhizzML type of `value`.
+fn value_type(value: &Value) -> Type {
+    match value {
+        Value::Bool(_) => Type::Boolean,
+        Value::Number(n) if n.is_i64() || n.is_u64() => Type::Integer,
+        Value::Number(_) => Type::Number,
+        Value::Array(_) => Type::List,
+        Value::Object(_) => Type::Map,
+        Value::String(_) | Value::Null => Type::String,
+    }
+}
+
+/// Parse a `--output-type` argument of the form "name=type".
+pub fn parse_output_type(s: &str) -> Result<(String, Type)> {
+    let (name, type_) = s.split_once('=').ok_or_else(|| {
+        format_err!("output type {:?} must have form \"name=type\"", s)
+    })?;
+    Ok((name.to_owned(), type_.parse()?))
+}
+
+#[test]
+fn guesses_value_types() {
+    assert_eq!(value_type(&Value::from(1)), Type::Integer);
+    assert_eq!(value_type(&Value::from(1.5)), Type::Number);
+    assert_eq!(value_type(&Value::from("x")), Type::String);
+    assert_eq!(
+        parse_output_type("n=integer").unwrap(),
+        ("n".to_owned(), Type::Integer),
+    );
+    assert!(parse_output_type("n").is_err());
+}
@@ -0,0 +1,47 @@
+//! Finding input resources using BigML tags.
+
+use anyhow::{format_err, Context, Result};
+use bigml::{
+    resource::{
+        BatchCentroid, BatchPrediction, Cluster, Dataset, Ensemble, Execution,
+        ListFilter, Resource, ResourceType, Source,
+    },
+    Client,
+};
+use futures::TryStreamExt;
+
+/// Return the IDs of all resources of type `resource_type` with `tag`.
+pub async fn tagged_resource_ids(
+    client: &Client,
+    resource_type: ResourceType,
+    tag: &str,
+) -> Result<Vec<String>> {
+    let filter = ListFilter::default().tag(tag);
+    let ids = match resource_type {
+        ResourceType::BatchCentroid => {
+            list_ids::<BatchCentroid>(client, &filter).await
+        }
+        ResourceType::BatchPrediction => {
+            list_ids::<BatchPrediction>(client, &filter).await
+        }
+        ResourceType::Cluster => list_ids::<Cluster>(client, &filter).await,
+        ResourceType::Dataset => list_ids::<Dataset>(client, &filter).await,
+        ResourceType::Ensemble => list_ids::<Ensemble>(client, &filter).await,
+        ResourceType::Execution => list_ids::<Execution>(client, &filter).await,
+        ResourceType::Source => list_ids::<Source>(client, &filter).await,
+        other => return Err(format_err!("cannot look up {} resources by tag", other)),
+    };
+    ids.with_context(|| format!("could not list resources tagged {:?}", tag))
+}
+
+/// List the IDs of all resources of type `R` matching `filter`.
+async fn list_ids<R: Resource>(
+    client: &Client,
+    filter: &ListFilter,
+) -> Result<Vec<String>> {
+    Ok(client
+        .list::<R>(filter)
+        .map_ok(|item| item.resource.to_string())
+        .try_collect()
+        .await?)
+}
@@ -2,7 +2,7 @@
 
 use anyhow::{Context, Result};
 use bigml::{
-    resource::{script, source, Execution, Resource, StatusCode},
+    resource::{script, source, Execution, Resource, Script, StatusCode},
     Client,
 };
 use cli_test_dir::*;
@@ -92,11 +92,10 @@ fn parallel_executions() {
             }
 
             // Upload our WhizzML script object.
-            let mut args = script::Args::new(WHIZZML_SCRIPT);
-            args.inputs
-                .push(script::Input::new("source", script::Type::ResourceId));
-            args.inputs
-                .push(script::Input::new("n", script::Type::Integer));
+            let args = Script::builder(WHIZZML_SCRIPT)
+                .input("source", script::Type::ResourceId)
+                .input("n", script::Type::Integer)
+                .build();
             let script = client.create_and_wait(&args).await?;
 
             Ok((sources, script.id().to_owned()))
@@ -114,13 +113,13 @@ fn parallel_executions() {
     // Run `bigml-parallel`.
     let output = testdir
         .cmd()
-        .args(&["-n", "bigml-parallel test"])
-        .args(&["-s", &script.to_string()])
-        .args(&["-R", "source"])
-        .args(&["-i", "n=2"])
-        .args(&["-o", "dataset"])
-        .args(&["-o", "n_times_2"])
-        .args(&["--tag", "bigml-parallel:test"])
+        .args(["-n", "bigml-parallel test"])
+        .args(["-s", script.as_str()])
+        .args(["-R", "source"])
+        .args(["-i", "n=2"])
+        .args(["-o", "dataset"])
+        .args(["-o", "n_times_2"])
+        .args(["--tag", "bigml-parallel:test"])
         .output_with_stdin(&input)
         .tee_output()
         .expect("error running bigml-parallel");
@@ -37,7 +37,7 @@ fn helper(
     // Prepare our execution arguments.
     let mut args = resource::execution::Args::default();
     args.set_script(resource::Id::<resource::Script>::from_str(script_id)?);
-    for &(ref name, ref value) in inputs {
+    for (name, value) in inputs {
         args.add_input(name.to_owned(), value)?;
     }
     for name in outputs {
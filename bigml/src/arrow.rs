@@ -0,0 +1,340 @@
+//! Converting between BigML data and Arrow `RecordBatch` values. Requires the
+//! `arrow` feature.
+
+use arrow_array::{
+    builder::{Float64Builder, StringBuilder},
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use csv::StringRecord;
+use futures::{stream, Stream, StreamExt};
+use std::{collections::HashMap, num::NonZeroUsize, sync::Arc};
+
+use crate::errors::*;
+use crate::resource::{
+    source::{self, Optype},
+    Dataset,
+};
+use crate::transport::{BoxStream, MaybeSend};
+
+/// Build an Arrow schema for a CSV download of `dataset` with the specified
+/// `headers`. Numeric fields become nullable `Float64` columns, and everything
+/// else becomes a nullable `Utf8` column.
+pub fn dataset_schema(dataset: &Dataset, headers: &StringRecord) -> SchemaRef {
+    fields_schema(&dataset.fields, headers)
+}
+
+/// Build an Arrow schema for CSV data with the specified `headers`, looking up
+/// the type of each column by name in `fields`.
+pub(crate) fn fields_schema(
+    fields: &HashMap<String, source::Field>,
+    headers: &StringRecord,
+) -> SchemaRef {
+    let columns = headers
+        .iter()
+        .map(|name| {
+            let optype = fields
+                .values()
+                .find(|field| field.name == name)
+                .map(|field| field.optype);
+            let data_type = match optype {
+                Some(Optype::Numeric) => DataType::Float64,
+                _ => DataType::Utf8,
+            };
+            Field::new(name, data_type, true)
+        })
+        .collect::<Vec<_>>();
+    Arc::new(Schema::new(columns))
+}
+
+/// Convert CSV `records` to a single `RecordBatch` using `schema`.
+/// `first_row` is the 1-based row number of the first record, used in error
+/// messages.
+pub(crate) fn records_to_batch(
+    schema: &SchemaRef,
+    records: &[StringRecord],
+    first_row: u64,
+) -> Result<RecordBatch> {
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+    for (i, field) in schema.fields().iter().enumerate() {
+        let values = records.iter().map(|record| record.get(i).unwrap_or(""));
+        let column: ArrayRef = match field.data_type() {
+            DataType::Float64 => {
+                let mut builder = Float64Builder::with_capacity(records.len());
+                for (offset, value) in values.enumerate() {
+                    if value.is_empty() {
+                        builder.append_null();
+                    } else {
+                        let value = value.parse::<f64>().map_err(|err| {
+                            Error::could_not_parse_row(first_row + offset as u64, err)
+                        })?;
+                        builder.append_value(value);
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            _ => {
+                let mut builder = StringBuilder::new();
+                for value in values {
+                    if value.is_empty() {
+                        builder.append_null();
+                    } else {
+                        builder.append_value(value);
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+        };
+        columns.push(column);
+    }
+    RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|err| Error::Other { source: err.into() })
+}
+
+/// Read the header from CSV `records`, and build a schema using the types in
+/// `fields`. Returns the schema and a stream of `RecordBatch` values of up to
+/// `batch_size` rows.
+pub(crate) async fn record_batches<S>(
+    fields: &HashMap<String, source::Field>,
+    records: S,
+    batch_size: NonZeroUsize,
+) -> Result<(
+    SchemaRef,
+    impl Stream<Item = Result<RecordBatch>> + MaybeSend + 'static,
+)>
+where
+    S: Stream<Item = Result<StringRecord>> + MaybeSend + 'static,
+{
+    let mut records: BoxStream<_> = Box::pin(records);
+    let schema = match records.next().await.transpose()? {
+        Some(headers) => fields_schema(fields, &headers),
+        None => Arc::new(Schema::empty()),
+    };
+    let state = (records.ready_chunks(batch_size.get()), 1u64);
+    let batch_schema = schema.clone();
+    let batches = stream::try_unfold(state, move |(mut chunks, row)| {
+        let schema = batch_schema.clone();
+        async move {
+            let chunk = match chunks.next().await {
+                Some(chunk) => chunk.into_iter().collect::<Result<Vec<_>>>()?,
+                None => return Ok(None),
+            };
+            let batch = records_to_batch(&schema, &chunk, row)?;
+            Ok(Some((batch, (chunks, row + chunk.len() as u64))))
+        }
+    });
+    Ok((schema, batches))
+}
+
+/// Convert `batch` to CSV, including a header if `header` is true.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn batch_to_csv(batch: &RecordBatch, header: bool) -> Result<Vec<u8>> {
+    let mut writer = arrow_csv::WriterBuilder::new()
+        .with_header(header)
+        .build(vec![]);
+    writer
+        .write(batch)
+        .map_err(|err| Error::Other { source: err.into() })?;
+    Ok(writer.into_inner())
+}
+
+/// Write `batches` to a new Parquet file at `path`. Requires the `parquet`
+/// feature.
+///
+/// We write to a temporary file next to `path` and rename it into place once
+/// we're done, so that a failed write never leaves a partial file at `path`.
+#[cfg(all(feature = "parquet", not(target_arch = "wasm32")))]
+pub(crate) async fn write_parquet<S>(
+    path: &std::path::Path,
+    schema: SchemaRef,
+    batches: S,
+) -> Result<()>
+where
+    S: Stream<Item = Result<RecordBatch>>,
+{
+    let mut partial_path = path.as_os_str().to_owned();
+    partial_path.push(".partial");
+    let partial_path = std::path::PathBuf::from(partial_path);
+
+    let result = write_parquet_to(&partial_path, path, schema, batches).await;
+    if result.is_err() {
+        // Our caller cares more about the original error than about
+        // whether we could clean up.
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        return result;
+    }
+    tokio::fs::rename(&partial_path, path)
+        .await
+        .map_err(|err| Error::could_not_write_file(path, err))
+}
+
+/// Write `batches` to a new Parquet file at `partial_path`, reporting errors
+/// as if we were writing to `path`.
+#[cfg(all(feature = "parquet", not(target_arch = "wasm32")))]
+async fn write_parquet_to<S>(
+    partial_path: &std::path::Path,
+    path: &std::path::Path,
+    schema: SchemaRef,
+    batches: S,
+) -> Result<()>
+where
+    S: Stream<Item = Result<RecordBatch>>,
+{
+    use futures::TryStreamExt;
+    use parquet::{arrow::AsyncArrowWriter, errors::ParquetError};
+
+    let write_err = |err: ParquetError| {
+        Error::could_not_write_file(path, Error::Other { source: err.into() })
+    };
+    let file = tokio::fs::File::create(partial_path)
+        .await
+        .map_err(|err| Error::could_not_write_file(path, err))?;
+    let mut writer =
+        AsyncArrowWriter::try_new(file, schema, None).map_err(write_err)?;
+    futures::pin_mut!(batches);
+    while let Some(batch) = batches.try_next().await? {
+        writer.write(&batch).await.map_err(write_err)?;
+    }
+    writer.close().await.map_err(write_err)?;
+    Ok(())
+}
+
+#[test]
+fn records_become_batches() {
+    use arrow_array::{Array, Float64Array, StringArray};
+    use futures::{executor::block_on, TryStreamExt};
+    use serde_json::json;
+
+    let dataset: Dataset = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "dev": null,
+        "description": "",
+        "name": "example",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "done" },
+        "columns": 2,
+        "excluded_fields": [],
+        "field_types": {},
+        "fields": {
+            "000000": { "name": "name", "optype": "categorical" },
+            "000001": { "name": "score", "optype": "numeric" },
+        },
+        "input_fields": ["000000", "000001"],
+        "rows": 3,
+    }))
+    .unwrap();
+    let records = vec![
+        StringRecord::from(vec!["name", "score"]),
+        StringRecord::from(vec!["a", "1.5"]),
+        StringRecord::from(vec!["b", ""]),
+        StringRecord::from(vec!["", "3"]),
+    ];
+    let batches = block_on(async {
+        let records = stream::iter(records.into_iter().map(Ok));
+        let (schema, batches) =
+            record_batches(&dataset.fields, records, NonZeroUsize::new(2).unwrap())
+                .await?;
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        batches.try_collect::<Vec<_>>().await
+    })
+    .unwrap();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].num_rows(), 2);
+    assert_eq!(batches[1].num_rows(), 1);
+
+    let names = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(names.value(1), "b");
+    let scores = batches[0]
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    assert_eq!(scores.value(0), 1.5);
+    assert!(scores.is_null(1));
+    assert!(batches[1].column(0).is_null(0));
+}
+
+#[test]
+fn batches_become_csv() {
+    use arrow_array::{Float64Array, StringArray};
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, true),
+        Field::new("score", DataType::Float64, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(StringArray::from(vec![Some("a, b"), None])),
+            Arc::new(Float64Array::from(vec![Some(1.5), Some(2.0)])),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        batch_to_csv(&batch, true).unwrap(),
+        b"name,score\n\"a, b\",1.5\n,2.0\n",
+    );
+    assert_eq!(
+        batch_to_csv(&batch, false).unwrap(),
+        b"\"a, b\",1.5\n,2.0\n"
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn batches_are_written_to_parquet() {
+    use arrow_array::Float64Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Float64, true)]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Float64Array::from(vec![Some(1.0), None]))],
+    )
+    .unwrap();
+    let path =
+        std::env::temp_dir().join(format!("bigml-{}.parquet", std::process::id()));
+    write_parquet(&path, schema, stream::iter(vec![Ok(batch.clone())]))
+        .await
+        .unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap();
+    let read = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read, vec![batch]);
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn failed_parquet_writes_leave_no_file() {
+    use arrow_array::Float64Array;
+
+    let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Float64, true)]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Float64Array::from(vec![Some(1.0)]))],
+    )
+    .unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("bigml-failed-{}.parquet", std::process::id()));
+    let batches = stream::iter(vec![
+        Ok(batch),
+        Err(Error::could_not_parse_row(2, "bad row")),
+    ]);
+    let err = write_parquet(&path, schema, batches).await.unwrap_err();
+    assert!(matches!(err, Error::CouldNotParseRow { row: 2, .. }));
+    assert!(!path.exists());
+    assert!(!path.with_extension("parquet.partial").exists());
+}
@@ -0,0 +1,128 @@
+//! Remembering responses, so that we can make conditional requests.
+
+use bytes::Bytes;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// The maximum number of responses we cache. Once we reach this, caching a
+/// new path evicts an arbitrary existing entry.
+const MAX_ENTRIES: usize = 1_000;
+
+/// A response body, plus the validators BigML sent with it.
+#[derive(Clone, Debug)]
+struct CachedResponse {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    body: Bytes,
+}
+
+/// The most recent response for each resource path that was returned with
+/// an `ETag` or `Last-Modified` header. This is shared between all the tasks
+/// using a `Client`.
+///
+/// We key entries on the path alone, because our URLs also contain our API
+/// key, and we'd rather not keep copies of that lying around.
+#[derive(Debug, Default)]
+pub(crate) struct ResponseCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl ResponseCache {
+    /// The headers we should send to ask BigML whether `path` has changed,
+    /// or an empty map if we have nothing cached for it.
+    pub(crate) fn conditional_headers(&self, path: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let entries = self.entries.lock().expect("lock poisoned");
+        if let Some(cached) = entries.get(path) {
+            if let Some(etag) = &cached.etag {
+                headers.insert(IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+        headers
+    }
+
+    /// The body we cached for `path`, if any. Call this when BigML returns
+    /// `304 Not Modified`.
+    pub(crate) fn body(&self, path: &str) -> Option<Bytes> {
+        let entries = self.entries.lock().expect("lock poisoned");
+        entries.get(path).map(|cached| cached.body.clone())
+    }
+
+    /// Forget anything we cached for `path`. Call this when the resource has
+    /// been deleted, or BigML says it doesn't exist.
+    pub(crate) fn remove(&self, path: &str) {
+        let mut entries = self.entries.lock().expect("lock poisoned");
+        entries.remove(path);
+    }
+
+    /// Remember `body` for `path`, if `headers` contain any validators. If
+    /// they don't, forget anything we cached earlier.
+    pub(crate) fn store(&self, path: &str, headers: &HeaderMap, body: &Bytes) {
+        let etag = headers.get(ETAG).cloned();
+        let last_modified = headers.get(LAST_MODIFIED).cloned();
+        let mut entries = self.entries.lock().expect("lock poisoned");
+        if etag.is_none() && last_modified.is_none() {
+            entries.remove(path);
+        } else {
+            if entries.len() >= MAX_ENTRIES && !entries.contains_key(path) {
+                let evicted = entries.keys().next().cloned();
+                if let Some(evicted) = evicted {
+                    entries.remove(&evicted);
+                }
+            }
+            entries.insert(
+                path.to_owned(),
+                CachedResponse {
+                    etag,
+                    last_modified,
+                    // Cloning `Bytes` only bumps a reference count.
+                    body: body.clone(),
+                },
+            );
+        }
+    }
+}
+
+#[test]
+fn sends_validators_for_cached_paths() {
+    let cache = ResponseCache::default();
+    let path = "source/5e5e5e5e5e5e5e5e5e5e5e5e";
+    assert!(cache.conditional_headers(path).is_empty());
+    assert!(cache.body(path).is_none());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+    cache.store(path, &headers, &Bytes::from_static(b"{}"));
+    let conditional = cache.conditional_headers(path);
+    assert_eq!(conditional.get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+    assert!(conditional.get(IF_MODIFIED_SINCE).is_none());
+    assert_eq!(cache.body(path).unwrap(), Bytes::from_static(b"{}"));
+
+    // A response without validators replaces the old entry.
+    cache.store(path, &HeaderMap::new(), &Bytes::from_static(b"{}"));
+    assert!(cache.conditional_headers(path).is_empty());
+    assert!(cache.body(path).is_none());
+
+    // Removing an entry forgets it.
+    cache.store(path, &headers, &Bytes::from_static(b"{}"));
+    cache.remove(path);
+    assert!(cache.conditional_headers(path).is_empty());
+    assert!(cache.body(path).is_none());
+}
+
+#[test]
+fn cache_size_is_limited() {
+    let cache = ResponseCache::default();
+    let mut headers = HeaderMap::new();
+    headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+    for i in 0..MAX_ENTRIES + 10 {
+        let path = format!("source/{:024x}", i);
+        cache.store(&path, &headers, &Bytes::from_static(b"{}"));
+    }
+    assert_eq!(cache.entries.lock().unwrap().len(), MAX_ENTRIES);
+}
@@ -0,0 +1,127 @@
+//! Support for declaring new resource types outside of this crate.
+
+/// Declare a new BigML resource type, for resources which this crate doesn't
+/// support yet.
+///
+/// This defines a struct with the standard `common`, `resource` and `status`
+/// fields, plus any fields you list, and implements [`Resource`] for it. The
+/// calling crate must depend on `serde` with the `derive` feature.
+///
+/// ```
+/// use bigml::{declare_resource, resource::Resource};
+///
+/// declare_resource! {
+///     #[api_name = "deepnet"]
+///     /// A BigML deepnet.
+///     pub struct Deepnet {
+///         /// The maximum number of training iterations.
+///         #[serde(default)]
+///         pub max_iterations: Option<u64>,
+///     }
+/// }
+///
+/// assert_eq!(Deepnet::id_prefix(), "deepnet/");
+/// ```
+///
+/// You can then use `Client::fetch`, `Client::list` and so on with your new
+/// type. To create resources, implement [`resource::Args`] for a type with
+/// `type Resource = Deepnet`.
+///
+/// [`Resource`]: crate::resource::Resource
+/// [`resource::Args`]: crate::resource::Args
+#[macro_export]
+macro_rules! declare_resource {
+    (
+        #[api_name = $api_name:literal]
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $field_ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, ::serde::Deserialize, ::serde::Serialize)]
+        $vis struct $name {
+            /// Common resource information. These fields will be serialized at
+            /// the top-level of this structure by `serde`.
+            #[serde(flatten)]
+            pub common: $crate::resource::ResourceCommon,
+
+            /// The ID of this resource.
+            pub resource: $crate::resource::Id<$name>,
+
+            /// The status of this resource.
+            pub status: $crate::resource::GenericStatus,
+
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $field_ty,
+            )*
+        }
+
+        impl $crate::resource::Resource for $name {
+            fn id_prefix() -> &'static str {
+                concat!($api_name, "/")
+            }
+
+            fn create_path() -> &'static str {
+                concat!("/", $api_name)
+            }
+
+            fn resource_type() -> $crate::resource::ResourceType {
+                $crate::resource::ResourceType::from_api_name($api_name)
+            }
+
+            fn common(&self) -> &$crate::resource::ResourceCommon {
+                &self.common
+            }
+
+            fn id(&self) -> &$crate::resource::Id<Self> {
+                &self.resource
+            }
+
+            fn status(&self) -> &dyn $crate::resource::Status {
+                &self.status
+            }
+        }
+    };
+}
+
+#[test]
+fn declared_resources_deserialize() {
+    use crate::resource::{Resource, ResourceType};
+    use serde_json::json;
+
+    declare_resource! {
+        #[api_name = "deepnet"]
+        /// A deepnet.
+        pub struct Deepnet {
+            /// The maximum number of training iterations.
+            pub max_iterations: u64,
+        }
+    }
+
+    let model: Deepnet = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "dev": null,
+        "description": "",
+        "name": "example",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "deepnet/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "done", "elapsed": 10 },
+        "max_iterations": 3,
+    }))
+    .unwrap();
+    assert_eq!(model.id().as_str(), "deepnet/5e5e5e5e5e5e5e5e5e5e5e5e");
+    assert_eq!(model.common().name, "example");
+    assert!(model.status().code().is_ready());
+    assert_eq!(model.max_iterations, 3);
+    assert_eq!(Deepnet::create_path(), "/deepnet");
+    assert_eq!(Deepnet::list_path(), "/deepnet");
+    assert_eq!(Deepnet::resource_type(), ResourceType::Deepnet);
+}
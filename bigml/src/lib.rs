@@ -17,7 +17,7 @@
 //! let username = "username";
 //! let api_key = "api_key";
 //! let path = Path::new("sample.csv");
-//! let script_id: Id<Script> = Id::from_str("script/123abc")?;
+//! let script_id: Id<Script> = Id::from_str("script/5e5e5e5e5e5e5e5e5e5e5e5e")?;
 //!
 //! // Create a BigML client.
 //! let client = bigml::Client::new(username, api_key)?;
@@ -41,6 +41,19 @@
 //!
 //! For more information, see the [BigML API][] and our [example code][].
 //!
+//! ### Cargo features
+//!
+//! Sources and datasets are always available. Other resource families can be
+//! turned off to speed up builds, and are all enabled by default:
+//!
+//! - `models`: models, ensembles, evaluations, predictions, batch predictions,
+//!   `local` and `pipeline`.
+//! - `anomalies`: anomaly detectors (implies `models`).
+//! - `clustering`: clusters and batch centroids.
+//! - `timeseries`: time series and forecasts.
+//! - `topicmodels`: topic models.
+//! - `whizzml`: scripts, libraries, executions and the `whizzml` module.
+//!
 //! [GitHub repository]: https://github.com/faradayio/bigml-rs
 //! [BigML API]: https://bigml.com/api
 //! [example code]: https://github.com/faradayio/bigml-rs/tree/master/examples
@@ -54,15 +67,43 @@
 
 #[macro_use]
 extern crate bigml_derive;
+// Let `#[derive(Resource)]` refer to `::bigml` from inside this crate, too.
+extern crate self as bigml;
 
-pub use client::{Client, DEFAULT_BIGML_DOMAIN};
+pub use client::{
+    Client, ClientBuilder, DEFAULT_BIGML_DOMAIN, FETCH_MANY_CONCURRENCY,
+};
 pub use errors::*;
-pub use progress::{ProgressCallback, ProgressOptions};
-pub use wait::WaitOptions;
+pub use progress::{
+    AsyncProgressCallback, ProgressCallback, ProgressOptions, StatusCallback,
+};
+pub use rows::DatasetRows;
+pub use transport::{
+    BoxFuture, BoxStream, HttpTransport, MaybeSend, ReqwestTransport, TransportError,
+    TransportFuture,
+};
+pub use wait::{Deadline, RetryBudget, RetryDecision, WaitOptions, WaitStats};
 
 #[macro_use]
 pub mod wait;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod cache;
 mod client;
+#[macro_use]
+mod declare;
 mod errors;
+#[cfg(feature = "models")]
+pub mod local;
+#[cfg(feature = "models")]
+pub mod pipeline;
 mod progress;
 pub mod resource;
+mod rows;
+#[cfg(any(test, feature = "test_support"))]
+pub mod test_support;
+mod throttle;
+mod time;
+mod transport;
+#[cfg(feature = "whizzml")]
+pub mod whizzml;
@@ -0,0 +1,217 @@
+//! Local anomaly scores using isolation forests.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::predicate::{field_ids, inputs_by_id, Test};
+use crate::errors::*;
+use crate::resource::anomaly::AnomalyNode;
+use crate::resource::{Anomaly, Id, Resource};
+
+/// The Euler–Mascheroni constant, used to estimate the average depth of
+/// unbuilt subtrees.
+const EULER_GAMMA: f64 = 0.577_215_664_9;
+
+/// An `Anomaly` detector which can compute anomaly scores without calling the
+/// BigML API.
+///
+/// ```no_run
+/// # use bigml::{Client, local::LocalAnomaly, resource::{Anomaly, Id}};
+/// # use serde_json::json;
+/// # use std::{collections::HashMap, str::FromStr};
+/// # async fn example(client: &Client) -> bigml::Result<()> {
+/// let id = Id::<Anomaly>::from_str("anomaly/5e5e5e5e5e5e5e5e5e5e5e5e")?;
+/// let anomaly = LocalAnomaly::new(&client.fetch(&id).await?)?;
+///
+/// let mut input = HashMap::new();
+/// input.insert("amount".to_owned(), json!(9999.0));
+/// if anomaly.score(&input) > 0.6 {
+///     println!("unusual!");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LocalAnomaly {
+    /// The anomaly detector we were created from.
+    id: Id<Anomaly>,
+    /// Maps field names to BigML field IDs.
+    field_ids: HashMap<String, String>,
+    /// The depth we expect a typical input to reach.
+    expected_mean_depth: f64,
+    /// The roots of our isolation trees.
+    trees: Vec<LocalAnomalyNode>,
+}
+
+impl LocalAnomaly {
+    /// Prepare a finished `anomaly` detector for local scoring.
+    pub fn new(anomaly: &Anomaly) -> Result<LocalAnomaly> {
+        let id = anomaly.id().to_owned();
+        let info = anomaly.model.as_ref().ok_or_else(|| {
+            Error::invalid_local_model(id.as_str(), "no anomaly detector data")
+        })?;
+        if info.trees.is_empty() {
+            return Err(Error::invalid_local_model(id.as_str(), "no trees"));
+        }
+
+        // BigML uses the smaller of the observed mean depth and the depth
+        // expected for a random tree built from `sample_size` instances.
+        let default_depth = anomaly.sample_size.filter(|n| *n > 1).map(|n| {
+            let n = n as f64;
+            2.0 * (EULER_GAMMA + (n - 1.0).ln() - (n - 1.0) / n)
+        });
+        let expected_mean_depth = match (info.mean_depth, default_depth) {
+            (Some(mean), Some(default)) => mean.min(default),
+            (Some(depth), None) | (None, Some(depth)) => depth,
+            (None, None) => {
+                return Err(Error::invalid_local_model(
+                    id.as_str(),
+                    "no mean_depth or sample_size",
+                ))
+            }
+        };
+
+        let trees = info
+            .trees
+            .iter()
+            .map(|tree| LocalAnomalyNode::new(id.as_str(), &tree.root))
+            .collect::<Result<_>>()?;
+        Ok(LocalAnomaly {
+            id,
+            field_ids: field_ids(&info.fields),
+            expected_mean_depth,
+            trees,
+        })
+    }
+
+    /// The ID of the anomaly detector we were created from.
+    pub fn id(&self) -> &Id<Anomaly> {
+        &self.id
+    }
+
+    /// Compute an anomaly score between 0.0 and 1.0 for `input`, which maps
+    /// either field names or BigML field IDs to values. Higher scores are more
+    /// unusual.
+    pub fn score(&self, input: &HashMap<String, Value>) -> f64 {
+        let by_id = inputs_by_id(&self.field_ids, input);
+        let depth_sum = self
+            .trees
+            .iter()
+            .map(|tree| tree.depth(&by_id))
+            .sum::<f64>();
+        let observed_mean_depth = depth_sum / self.trees.len() as f64;
+        2f64.powf(-observed_mean_depth / self.expected_mean_depth)
+    }
+}
+
+/// A node in an isolation tree, with pre-parsed predicates.
+#[derive(Clone, Debug)]
+struct LocalAnomalyNode {
+    tests: Vec<Test>,
+    population: u64,
+    children: Vec<LocalAnomalyNode>,
+}
+
+impl LocalAnomalyNode {
+    /// Convert `node` and its children, reporting errors as belonging to
+    /// `anomaly_id`.
+    fn new(anomaly_id: &str, node: &AnomalyNode) -> Result<LocalAnomalyNode> {
+        Ok(LocalAnomalyNode {
+            tests: node
+                .predicates
+                .iter()
+                .map(|predicate| Test::new(anomaly_id, predicate))
+                .collect::<Result<_>>()?,
+            population: node.population,
+            children: node
+                .children
+                .iter()
+                .map(|child| LocalAnomalyNode::new(anomaly_id, child))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Does `input` satisfy all of our predicates?
+    fn matches(&self, input: &HashMap<&str, &Value>) -> bool {
+        self.tests.iter().all(|test| test.matches(input))
+    }
+
+    /// How deep does `input` go in the tree rooted at this node? If we reach a
+    /// leaf containing several training instances, we add the expected depth
+    /// of the subtree which would have isolated them.
+    fn depth(&self, input: &HashMap<&str, &Value>) -> f64 {
+        if !self.matches(input) {
+            return 0.0;
+        }
+        let mut depth = 1.0;
+        let mut node = self;
+        while !node.children.is_empty() {
+            match node.children.iter().find(|child| child.matches(input)) {
+                Some(child) => {
+                    depth += 1.0;
+                    node = child;
+                }
+                None => return depth,
+            }
+        }
+        if node.population > 1 {
+            let population = node.population as f64;
+            depth += 2.0 * ((population - 1.0).ln() + EULER_GAMMA)
+                - 2.0 * (population - 1.0) / population;
+        }
+        depth
+    }
+}
+
+#[test]
+fn scores_using_isolation_forest() {
+    use serde_json::json;
+
+    let anomaly: Anomaly = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "payments",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "anomaly/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The anomaly detector has been created" },
+        "sample_size": 64,
+        "model": {
+            "fields": {
+                "000000": { "name": "amount", "optype": "numeric" }
+            },
+            "mean_depth": 6.0,
+            "trees": [{
+                "root": {
+                    "predicates": [true],
+                    "population": 64,
+                    "children": [
+                        {
+                            "predicates": [{ "field": "000000", "op": ">", "value": 1000 }],
+                            "population": 1
+                        },
+                        {
+                            "predicates": [{ "field": "000000", "op": "<=", "value": 1000 }],
+                            "population": 63
+                        }
+                    ]
+                }
+            }]
+        }
+    }))
+    .unwrap();
+    let local = LocalAnomaly::new(&anomaly).unwrap();
+    let score = |amount: f64| {
+        let mut input = HashMap::new();
+        input.insert("amount".to_owned(), json!(amount));
+        local.score(&input)
+    };
+
+    // An isolated outlier has depth 2, which is much less than expected.
+    assert!((score(5000.0) - 2f64.powf(-2.0 / 6.0)).abs() < 1e-9);
+    // A typical value ends up in a big leaf, so it's much deeper.
+    assert!(score(10.0) < 0.5);
+    assert!(score(5000.0) > score(10.0));
+}
@@ -0,0 +1,14 @@
+//! Tools for making predictions locally, using downloaded resources.
+//!
+//! Calling the BigML API once per row adds a lot of latency. Instead, you can
+//! fetch a finished resource once using `Client::fetch`, and then evaluate it
+//! here for as many rows as you like.
+
+#[cfg(feature = "anomalies")]
+mod anomaly;
+mod model;
+mod predicate;
+
+#[cfg(feature = "anomalies")]
+pub use self::anomaly::LocalAnomaly;
+pub use self::model::{LocalModel, LocalPrediction};
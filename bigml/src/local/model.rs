@@ -0,0 +1,220 @@
+//! Local predictions using decision tree models.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::predicate::{field_ids, inputs_by_id, Test};
+use crate::errors::*;
+use crate::resource::model::Node;
+use crate::resource::{Id, Model, Resource};
+
+/// A decision tree `Model` which can make predictions without calling the
+/// BigML API.
+///
+/// ```no_run
+/// # use bigml::{Client, local::LocalModel, resource::{Id, Model}};
+/// # use serde_json::json;
+/// # use std::{collections::HashMap, str::FromStr};
+/// # async fn example(client: &Client) -> bigml::Result<()> {
+/// let id = Id::<Model>::from_str("model/5e5e5e5e5e5e5e5e5e5e5e5e")?;
+/// let model = LocalModel::new(&client.fetch(&id).await?)?;
+///
+/// let mut input = HashMap::new();
+/// input.insert("petal length".to_owned(), json!(4.2));
+/// let prediction = model.predict(&input);
+/// println!("{} ({:?})", prediction.output, prediction.confidence);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LocalModel {
+    /// The model we were created from.
+    id: Id<Model>,
+    /// Maps field names to BigML field IDs.
+    field_ids: HashMap<String, String>,
+    /// The root of our decision tree.
+    root: LocalNode,
+}
+
+impl LocalModel {
+    /// Prepare a finished `model` for local predictions.
+    pub fn new(model: &Model) -> Result<LocalModel> {
+        let id = model.id().to_owned();
+        let info = model
+            .model
+            .as_ref()
+            .ok_or_else(|| Error::invalid_local_model(id.as_str(), "no model data"))?;
+        let root = info
+            .root
+            .as_ref()
+            .ok_or_else(|| Error::invalid_local_model(id.as_str(), "no root node"))?;
+        let root = LocalNode::new(id.as_str(), root)?;
+        Ok(LocalModel {
+            id,
+            field_ids: field_ids(&info.fields),
+            root,
+        })
+    }
+
+    /// The ID of the model we were created from.
+    pub fn id(&self) -> &Id<Model> {
+        &self.id
+    }
+
+    /// Predict an output for `input`, which maps either field names or BigML
+    /// field IDs to values.
+    ///
+    /// Like BigML, if a field used by the tree is missing from `input`, we
+    /// stop at the last node we reached, and return its prediction.
+    pub fn predict(&self, input: &HashMap<String, Value>) -> LocalPrediction {
+        let by_id = inputs_by_id(&self.field_ids, input);
+        let mut node = &self.root;
+        'descend: loop {
+            for child in &node.children {
+                if child.test.matches(&by_id) {
+                    node = child;
+                    continue 'descend;
+                }
+            }
+            return node.prediction.clone();
+        }
+    }
+}
+
+/// A prediction made by a `LocalModel`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LocalPrediction {
+    /// The predicted value.
+    pub output: Value,
+
+    /// How confident we are in `output`. Only present for classification
+    /// models.
+    pub confidence: Option<f64>,
+
+    /// The number of training instances which reached the node making this
+    /// prediction.
+    pub count: Option<u64>,
+}
+
+/// A decision tree node, with a pre-parsed predicate.
+#[derive(Clone, Debug)]
+struct LocalNode {
+    test: Test,
+    prediction: LocalPrediction,
+    children: Vec<LocalNode>,
+}
+
+impl LocalNode {
+    /// Convert `node` and its children, reporting errors as belonging to
+    /// `model_id`.
+    fn new(model_id: &str, node: &Node) -> Result<LocalNode> {
+        Ok(LocalNode {
+            test: Test::new(model_id, &node.predicate)?,
+            prediction: LocalPrediction {
+                output: node.output.clone(),
+                confidence: node.confidence,
+                count: node.count,
+            },
+            children: node
+                .children
+                .iter()
+                .map(|child| LocalNode::new(model_id, child))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+#[test]
+fn predicts_using_tree() {
+    use serde_json::json;
+
+    let model: Model = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "model/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The model has been created" },
+        "objective_fields": ["000004"],
+        "model": {
+            "fields": {
+                "000002": { "name": "petal length", "optype": "numeric" },
+                "000003": { "name": "notes", "optype": "text" },
+                "000004": { "name": "species", "optype": "categorical" }
+            },
+            "root": {
+                "predicate": true,
+                "output": "Iris-versicolor",
+                "confidence": 0.3,
+                "count": 150,
+                "children": [
+                    {
+                        "predicate": { "field": "000002", "operator": "<=", "value": 2.45 },
+                        "output": "Iris-setosa",
+                        "confidence": 0.9,
+                        "count": 50
+                    },
+                    {
+                        "predicate": { "field": "000002", "operator": ">", "value": 2.45 },
+                        "output": "Iris-versicolor",
+                        "confidence": 0.5,
+                        "count": 100,
+                        "children": [
+                            {
+                                "predicate": {
+                                    "field": "000003",
+                                    "operator": ">",
+                                    "value": 0,
+                                    "term": "purple"
+                                },
+                                "output": "Iris-virginica",
+                                "confidence": 0.8,
+                                "count": 40
+                            },
+                            {
+                                "predicate": {
+                                    "field": "000003",
+                                    "operator": "<=*",
+                                    "value": 0,
+                                    "term": "purple"
+                                },
+                                "output": "Iris-versicolor",
+                                "confidence": 0.7,
+                                "count": 60
+                            }
+                        ]
+                    }
+                ]
+            }
+        }
+    }))
+    .unwrap();
+    let local = LocalModel::new(&model).unwrap();
+
+    let predict = |input: Value| {
+        let input = serde_json::from_value::<HashMap<String, Value>>(input).unwrap();
+        local.predict(&input).output
+    };
+    assert_eq!(
+        predict(json!({ "petal length": 1.0 })),
+        json!("Iris-setosa")
+    );
+    assert_eq!(predict(json!({ "000002": "1.0" })), json!("Iris-setosa"));
+    assert_eq!(
+        predict(json!({ "petal length": 5.0, "notes": "Purple petals" })),
+        json!("Iris-virginica"),
+    );
+    assert_eq!(
+        predict(json!({ "petal length": 5.0, "notes": "" })),
+        json!("Iris-versicolor"),
+    );
+    // With no petal length, we stop at the root.
+    let prediction = local.predict(&HashMap::new());
+    assert_eq!(prediction.output, json!("Iris-versicolor"));
+    assert_eq!(prediction.confidence, Some(0.3));
+    assert_eq!(prediction.count, Some(150));
+}
@@ -0,0 +1,196 @@
+//! Predicates shared by our local tree-based models.
+
+use serde_json::Value;
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::errors::*;
+use crate::resource::model::{Predicate, Split};
+use crate::resource::source::Field;
+
+/// Map the names of `fields` to their BigML field IDs.
+pub(super) fn field_ids(fields: &HashMap<String, Field>) -> HashMap<String, String> {
+    fields
+        .iter()
+        .map(|(field_id, field)| (field.name.clone(), field_id.clone()))
+        .collect()
+}
+
+/// Convert `input`, which maps field names or BigML field IDs to values, to a
+/// map from field IDs to values, leaving out missing values.
+pub(super) fn inputs_by_id<'a>(
+    field_ids: &'a HashMap<String, String>,
+    input: &'a HashMap<String, Value>,
+) -> HashMap<&'a str, &'a Value> {
+    let mut by_id = HashMap::with_capacity(input.len());
+    for (key, value) in input {
+        let field_id = field_ids.get(key).unwrap_or(key);
+        if !is_missing(value) {
+            by_id.insert(field_id.as_str(), value);
+        }
+    }
+    by_id
+}
+
+/// A comparison operator used in a `Split`.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Operator {
+    Lt,
+    Lte,
+    Eq,
+    Ne,
+    Gte,
+    Gt,
+}
+
+impl Operator {
+    /// Does `ordering` (of an input value relative to the split value)
+    /// satisfy this operator?
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Lte => ordering != Ordering::Greater,
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Ne => ordering != Ordering::Equal,
+            Operator::Gte => ordering != Ordering::Less,
+            Operator::Gt => ordering == Ordering::Greater,
+        }
+    }
+}
+
+/// A pre-parsed `Predicate`.
+#[derive(Clone, Debug)]
+pub(super) enum Test {
+    /// Always returns this value.
+    Constant(bool),
+    /// Is the field missing?
+    Missing { field: String, missing: bool },
+    /// Compare the field (or the number of times a term appears in it) to a
+    /// value.
+    Compare {
+        field: String,
+        operator: Operator,
+        /// Is this test true when the field is missing?
+        or_missing: bool,
+        value: Value,
+        term: Option<String>,
+    },
+}
+
+impl Test {
+    /// Parse `predicate`, reporting errors as belonging to `model_id`.
+    pub(super) fn new(model_id: &str, predicate: &Predicate) -> Result<Test> {
+        let split: &Split = match predicate {
+            Predicate::Constant(value) => return Ok(Test::Constant(*value)),
+            Predicate::Split(split) => split,
+        };
+        let (op, or_missing) = match split.operator.strip_suffix('*') {
+            Some(op) => (op, true),
+            None => (split.operator.as_str(), false),
+        };
+        let operator = match op {
+            "<" => Operator::Lt,
+            "<=" => Operator::Lte,
+            "=" => Operator::Eq,
+            "!=" => Operator::Ne,
+            ">=" => Operator::Gte,
+            ">" => Operator::Gt,
+            _ => {
+                return Err(Error::invalid_local_model(
+                    model_id,
+                    format!("unsupported operator {:?}", split.operator),
+                ))
+            }
+        };
+        let field = split.field.clone();
+        match (operator, &split.value) {
+            (Operator::Eq, Value::Null) => Ok(Test::Missing {
+                field,
+                missing: true,
+            }),
+            (Operator::Ne, Value::Null) => Ok(Test::Missing {
+                field,
+                missing: false,
+            }),
+            (_, value) => Ok(Test::Compare {
+                field,
+                operator,
+                or_missing,
+                value: value.clone(),
+                term: split.term.clone(),
+            }),
+        }
+    }
+
+    /// Is this test true for `input`, which maps field IDs to values?
+    pub(super) fn matches(&self, input: &HashMap<&str, &Value>) -> bool {
+        match self {
+            Test::Constant(value) => *value,
+            Test::Missing { field, missing } => {
+                input.contains_key(field.as_str()) != *missing
+            }
+            Test::Compare {
+                field,
+                operator,
+                or_missing,
+                value,
+                term,
+            } => {
+                let input = match input.get(field.as_str()) {
+                    Some(input) => *input,
+                    None => return *or_missing,
+                };
+                let ordering = match term {
+                    Some(term) => {
+                        let count = term_count(&value_to_string(input), term);
+                        value.as_f64().and_then(|v| count.partial_cmp(&v))
+                    }
+                    None => compare(input, value),
+                };
+                ordering.is_some_and(|ordering| operator.accepts(ordering))
+            }
+        }
+    }
+}
+
+/// Is `value` missing, as far as BigML is concerned?
+fn is_missing(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+/// Compare an `input` value to a split `value`. Numbers may be passed as
+/// strings.
+fn compare(input: &Value, value: &Value) -> Option<Ordering> {
+    match value {
+        Value::Number(n) => {
+            let input = match input {
+                Value::Number(input) => input.as_f64(),
+                Value::String(input) => input.trim().parse::<f64>().ok(),
+                _ => None,
+            }?;
+            input.partial_cmp(&n.as_f64()?)
+        }
+        _ => Some(value_to_string(input).cmp(&value_to_string(value))),
+    }
+}
+
+/// Convert `value` to a string, without quoting strings.
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_owned(),
+        other => other.to_string(),
+    }
+}
+
+/// Count the number of times `term` appears as a word in `text`, ignoring
+/// case.
+fn term_count(text: &str, term: &str) -> f64 {
+    let term = term.to_lowercase();
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| *word == term)
+        .count() as f64
+}
@@ -0,0 +1,410 @@
+//! Declarative pipelines of BigML resources.
+//!
+//! A `Pipeline` describes a set of resources to create, where each resource
+//! may depend on earlier ones, like source → dataset → model → evaluation.
+//! Each step returns a typed `Step<R>` handle, which can only be passed to
+//! later steps expecting an `R`, so mistakes like training a model on a
+//! source are caught at compile time.
+//!
+//! ```no_run
+//! # use bigml::{Client, WaitOptions, pipeline::{Pipeline, PipelineState}};
+//! # use bigml::resource::{evaluation::ClassificationResult, source};
+//! # async fn example(client: &Client) -> bigml::Result<()> {
+//! let mut pipeline = Pipeline::new();
+//! let train_src = pipeline.source("train-source", source::Args::remote("s3://bucket/train.csv"))?;
+//! let test_src = pipeline.source("test-source", source::Args::remote("s3://bucket/test.csv"))?;
+//! let train = pipeline.dataset("train", &train_src)?;
+//! let test = pipeline.dataset("test", &test_src)?;
+//! let model = pipeline.model("model", &train)?;
+//! let evaluation = pipeline.evaluation::<ClassificationResult>("evaluation", &model, &test)?;
+//! let predictions = pipeline.batch_prediction("predictions", &model, &test)?;
+//!
+//! // If this fails, save `state` and pass it to `run` again later to resume.
+//! let mut state = PipelineState::default();
+//! pipeline.run(client, &WaitOptions::default(), &mut state).await?;
+//! println!("evaluation: {}", state.get(&evaluation).unwrap());
+//! println!("predictions: {}", state.get(&predictions).unwrap());
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, marker::PhantomData, str::FromStr, sync::Arc};
+
+use crate::client::Client;
+use crate::errors::*;
+use crate::progress::ProgressOptions;
+use crate::resource::{
+    self, batchprediction, dataset, evaluation, model, source, BatchPrediction,
+    Dataset, Evaluation, Id, Model, Resource, Source,
+};
+use crate::transport::BoxFuture;
+use crate::wait::WaitOptions;
+
+/// A typed handle to a step in a `Pipeline`, which will create a resource of
+/// type `R`.
+pub struct Step<R: Resource> {
+    /// The name of this step.
+    name: String,
+    /// Placeholder to allow us to have a type parameter.
+    _phantom: PhantomData<R>,
+}
+
+impl<R: Resource> Step<R> {
+    /// The name of this step.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// We implement these manually, because `#[derive]` would require `R: Clone`.
+impl<R: Resource> Clone for Step<R> {
+    fn clone(&self) -> Self {
+        Step {
+            name: self.name.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: Resource> fmt::Debug for Step<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Step<{}>({:?})", R::resource_type(), self.name)
+    }
+}
+
+/// The IDs of the resources created by a `Pipeline`, keyed by step name.
+///
+/// This can be serialized and stored, so that a failed pipeline can be resumed
+/// later without re-creating the resources it has already created.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PipelineState {
+    /// The ID created by each step, keyed by step name.
+    ids: BTreeMap<String, String>,
+}
+
+impl PipelineState {
+    /// The ID of the resource created by `step`, if it has been created.
+    pub fn get<R: Resource>(&self, step: &Step<R>) -> Option<Id<R>> {
+        self.ids
+            .get(&step.name)
+            .and_then(|id| Id::from_str(id).ok())
+    }
+
+    /// Forget the resource created by `step`, so that it will be created again
+    /// the next time the pipeline runs. This is useful if the resource
+    /// failed.
+    pub fn remove<R: Resource>(&mut self, step: &Step<R>) {
+        self.ids.remove(&step.name);
+    }
+
+    /// The ID created by the step named `name`.
+    fn id<R: Resource>(&self, name: &str) -> Result<Id<R>> {
+        let id = self.ids.get(name).ok_or_else(|| {
+            Error::invalid_pipeline_step(name, "this step has not been run")
+        })?;
+        Id::from_str(id)
+    }
+}
+
+/// Create a resource, using the IDs of earlier steps, and return its ID.
+type CreateFn = dyn for<'a> Fn(&'a Client, &'a PipelineState) -> BoxFuture<'a, Result<String>>
+    + Send
+    + Sync;
+
+/// Wait for the resource with the specified ID.
+type WaitFn =
+    for<'a> fn(&'a Client, &'a str, &'a WaitOptions) -> BoxFuture<'a, Result<()>>;
+
+/// A single step in a `Pipeline`.
+struct StepInfo {
+    /// The name of this step.
+    name: String,
+    /// How to create this step's resource.
+    create: Arc<CreateFn>,
+    /// How to wait for this step's resource.
+    wait: WaitFn,
+}
+
+/// A set of BigML resources to create, and the dependencies between them.
+/// See the [module documentation](self) for an example.
+#[derive(Default)]
+pub struct Pipeline {
+    /// Our steps, in the order they were declared. Since steps can only depend
+    /// on earlier steps, this is always a valid order in which to run them.
+    steps: Vec<StepInfo>,
+}
+
+impl Pipeline {
+    /// Create a new, empty pipeline.
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Add a step which creates a resource using `args`, without depending on
+    /// any other steps.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline already has a step named `name`.
+    pub fn step<A>(&mut self, name: &str, args: A) -> Result<Step<A::Resource>>
+    where
+        A: resource::Args + Send + Sync + 'static,
+    {
+        let args = Arc::new(args);
+        self.add_step(name, &[], move |client, _state| {
+            let args = args.clone();
+            Box::pin(async move { Ok(client.create(&*args).await?.id().to_string()) })
+        })
+    }
+
+    /// Add a step which creates a resource using arguments returned by
+    /// `make_args`, which is passed the ID created by `parent`.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline already has a step named `name`, or if `parent` is not
+    /// a step in this pipeline.
+    pub fn step_from<P, A, F>(
+        &mut self,
+        name: &str,
+        parent: &Step<P>,
+        make_args: F,
+    ) -> Result<Step<A::Resource>>
+    where
+        P: Resource,
+        A: resource::Args + Send + Sync + 'static,
+        F: Fn(Id<P>) -> A + Send + Sync + 'static,
+    {
+        let dependencies = [parent.name.as_str()];
+        let parent = parent.name.clone();
+        self.add_step(name, &dependencies, move |client, state| {
+            let args = state.id::<P>(&parent).map(&make_args);
+            Box::pin(async move { Ok(client.create(&args?).await?.id().to_string()) })
+        })
+    }
+
+    /// Add a step which creates a resource using arguments returned by
+    /// `make_args`, which is passed the IDs created by `first` and `second`.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline already has a step named `name`, or if `first` or
+    /// `second` is not a step in this pipeline.
+    pub fn step_from2<P1, P2, A, F>(
+        &mut self,
+        name: &str,
+        first: &Step<P1>,
+        second: &Step<P2>,
+        make_args: F,
+    ) -> Result<Step<A::Resource>>
+    where
+        P1: Resource,
+        P2: Resource,
+        A: resource::Args + Send + Sync + 'static,
+        F: Fn(Id<P1>, Id<P2>) -> A + Send + Sync + 'static,
+    {
+        let dependencies = [first.name.as_str(), second.name.as_str()];
+        let first = first.name.clone();
+        let second = second.name.clone();
+        self.add_step(name, &dependencies, move |client, state| {
+            let args = state
+                .id::<P1>(&first)
+                .and_then(|a| Ok(make_args(a, state.id::<P2>(&second)?)));
+            Box::pin(async move { Ok(client.create(&args?).await?.id().to_string()) })
+        })
+    }
+
+    /// Add a step which creates a source.
+    pub fn source(&mut self, name: &str, args: source::Args) -> Result<Step<Source>> {
+        self.step(name, args)
+    }
+
+    /// Add a step which creates a dataset from `source`.
+    pub fn dataset(
+        &mut self,
+        name: &str,
+        source: &Step<Source>,
+    ) -> Result<Step<Dataset>> {
+        self.step_from(name, source, dataset::Args::from_source)
+    }
+
+    /// Add a step which trains a model on `dataset`.
+    pub fn model(
+        &mut self,
+        name: &str,
+        dataset: &Step<Dataset>,
+    ) -> Result<Step<Model>> {
+        self.step_from(name, dataset, model::Args::from_dataset)
+    }
+
+    /// Add a step which evaluates `model` against `dataset`.
+    pub fn evaluation<R: evaluation::Result>(
+        &mut self,
+        name: &str,
+        model: &Step<Model>,
+        dataset: &Step<Dataset>,
+    ) -> Result<Step<Evaluation<R>>> {
+        self.step_from2(name, model, dataset, evaluation::Args::<R>::new)
+    }
+
+    /// Add a step which uses `model` to make predictions for `dataset`.
+    pub fn batch_prediction(
+        &mut self,
+        name: &str,
+        model: &Step<Model>,
+        dataset: &Step<Dataset>,
+    ) -> Result<Step<BatchPrediction>> {
+        self.step_from2(name, model, dataset, batchprediction::Args::new)
+    }
+
+    /// Register a step which creates an `R` using `create`, after the steps
+    /// named in `dependencies`.
+    fn add_step<R, F>(
+        &mut self,
+        name: &str,
+        dependencies: &[&str],
+        create: F,
+    ) -> Result<Step<R>>
+    where
+        R: Resource,
+        F: for<'a> Fn(&'a Client, &'a PipelineState) -> BoxFuture<'a, Result<String>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        if self.steps.iter().any(|step| step.name == name) {
+            return Err(Error::invalid_pipeline_step(
+                name,
+                "the pipeline already has a step with this name",
+            ));
+        }
+        for dependency in dependencies {
+            if self.steps.iter().all(|step| step.name != *dependency) {
+                return Err(Error::invalid_pipeline_step(
+                    name,
+                    format!(
+                        "depends on {:?}, which is not in this pipeline",
+                        dependency
+                    ),
+                ));
+            }
+        }
+        self.steps.push(StepInfo {
+            name: name.to_owned(),
+            create: Arc::new(create),
+            wait: wait_for::<R>,
+        });
+        Ok(Step {
+            name: name.to_owned(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Create and wait for each resource in this pipeline, recording their IDs
+    /// in `state`. Any timeout in `options` applies to the whole pipeline.
+    ///
+    /// Steps which already have an ID in `state` are not created again. We
+    /// still wait for them, in case they weren't finished when a previous run
+    /// failed. If a resource itself failed, use `PipelineState::remove` to
+    /// create it again.
+    pub async fn run(
+        &self,
+        client: &Client,
+        options: &WaitOptions,
+        state: &mut PipelineState,
+    ) -> Result<()> {
+        let options = options.with_fixed_deadline();
+        for step in &self.steps {
+            let id = match state.ids.get(&step.name) {
+                Some(id) => id.to_owned(),
+                None => {
+                    let id = (step.create)(client, state).await?;
+                    state.ids.insert(step.name.clone(), id.clone());
+                    id
+                }
+            };
+            (step.wait)(client, &id, &options).await?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field(
+                "steps",
+                &self.steps.iter().map(|step| &step.name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Wait for the resource `id` of type `R`.
+fn wait_for<'a, R: Resource>(
+    client: &'a Client,
+    id: &'a str,
+    options: &'a WaitOptions,
+) -> BoxFuture<'a, Result<()>> {
+    Box::pin(async move {
+        let id = Id::<R>::from_str(id)?;
+        let mut progress_options = ProgressOptions::default();
+        client.wait_opt(&id, options, &mut progress_options).await?;
+        Ok(())
+    })
+}
+
+#[test]
+fn steps_are_typed_and_named() {
+    use crate::resource::evaluation::ClassificationResult;
+
+    let mut pipeline = Pipeline::new();
+    let src = pipeline
+        .source("src", source::Args::data("a,b\n1,2\n"))
+        .unwrap();
+    let data = pipeline.dataset("data", &src).unwrap();
+    let model = pipeline.model("model", &data).unwrap();
+    let eval = pipeline
+        .evaluation::<ClassificationResult>("eval", &model, &data)
+        .unwrap();
+    assert_eq!(eval.name(), "eval");
+    assert_eq!(format!("{:?}", model), "Step<model>(\"model\")");
+    assert_eq!(
+        format!("{:?}", pipeline),
+        r#"Pipeline { steps: ["src", "data", "model", "eval"] }"#,
+    );
+
+    let mut state = PipelineState::default();
+    state.ids.insert(
+        "model".to_owned(),
+        "model/5e5e5e5e5e5e5e5e5e5e5e5e".to_owned(),
+    );
+    assert_eq!(
+        state.get(&model).unwrap().as_str(),
+        "model/5e5e5e5e5e5e5e5e5e5e5e5e",
+    );
+    assert!(state.get(&data).is_none());
+    assert!(state.id::<Dataset>("data").is_err());
+    state.remove(&model);
+    assert!(state.get(&model).is_none());
+}
+
+#[test]
+fn invalid_steps_are_rejected() {
+    let mut pipeline = Pipeline::new();
+    let src = pipeline
+        .source("src", source::Args::data("a\n1\n"))
+        .unwrap();
+    let err = pipeline
+        .source("src", source::Args::data("a\n2\n"))
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidPipelineStep { .. }));
+
+    // Steps from another pipeline don't exist here.
+    let mut other = Pipeline::new();
+    let other_src = other.source("other", source::Args::data("a\n3\n")).unwrap();
+    let err = pipeline.dataset("data", &other_src).unwrap_err();
+    assert!(matches!(err, Error::InvalidPipelineStep { .. }));
+    assert!(pipeline.dataset("data", &src).is_ok());
+}
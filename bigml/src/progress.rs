@@ -1,14 +1,37 @@
 //! Code used for reporting execution progress.
 
 use crate::errors::*;
+use crate::resource::StatusCode;
+use crate::transport::BoxFuture;
 
 /// A callback which we be callled every time we have a new `T` value.
 pub type ProgressCallback<'a, T> = dyn (FnMut(&T) -> Result<()>) + Send + Sync + 'a;
 
+/// An asynchronous callback which will be called every time we have a new `T`
+/// value. The returned future will be awaited before we continue waiting, so
+/// it may perform I/O without blocking the wait loop. The future may not
+/// borrow the `T` value, so clone anything it needs. Under WebAssembly, the
+/// future doesn't need to be `Send`.
+pub type AsyncProgressCallback<'a, T> =
+    dyn (FnMut(&T) -> BoxFuture<'a, Result<()>>) + Send + Sync + 'a;
+
+/// A callback which will be called with the status code, progress and
+/// message of each new value. Unlike `ProgressCallback`, this doesn't depend
+/// on the type of resource, so one progress reporter can be used everywhere.
+pub type StatusCallback<'a> =
+    dyn (FnMut(StatusCode, Option<f32>, &str) -> Result<()>) + Send + Sync + 'a;
+
 /// Options specifying how to report progress.
 pub struct ProgressOptions<'a, T: 'static> {
     /// Our callback value. Only accessible from inside this crate.
     pub(crate) callback: Option<&'a mut ProgressCallback<'a, T>>,
+
+    /// Our asynchronous callback value. Only accessible from inside this
+    /// crate.
+    pub(crate) async_callback: Option<&'a mut AsyncProgressCallback<'a, T>>,
+
+    /// Our status callback value. Only accessible from inside this crate.
+    pub(crate) status_callback: Option<&'a mut StatusCallback<'a>>,
 }
 
 impl<'a, T: 'static> ProgressOptions<'a, T> {
@@ -17,10 +40,60 @@ impl<'a, T: 'static> ProgressOptions<'a, T> {
         self.callback = Some(callback);
         self
     }
+
+    /// Specify an asynchronous callback to be called whenever we see a new `T`
+    /// value. If both `callback` and `async_callback` are specified, both will
+    /// be called.
+    ///
+    /// ```
+    /// use bigml::{resource::Source, ProgressOptions};
+    /// use futures::FutureExt;
+    ///
+    /// let mut report = |source: &Source| {
+    ///     let message = source.status.common.message.clone();
+    ///     async move {
+    ///         // Send `message` somewhere.
+    ///         println!("{}", message);
+    ///         Ok(())
+    ///     }
+    ///     .boxed()
+    /// };
+    /// let options = ProgressOptions::default().async_callback(&mut report);
+    /// ```
+    pub fn async_callback(
+        mut self,
+        callback: &'a mut AsyncProgressCallback<'a, T>,
+    ) -> Self {
+        self.async_callback = Some(callback);
+        self
+    }
+
+    /// Specify a callback to be called with the status code, progress and
+    /// message whenever we see a new `T` value. This may be combined with
+    /// the other callbacks.
+    ///
+    /// ```
+    /// use bigml::{resource::Source, ProgressOptions};
+    ///
+    /// let mut report = |code, progress: Option<f32>, message: &str| {
+    ///     let percent = progress.unwrap_or(0.0) * 100.0;
+    ///     eprintln!("{:?} {:.0}% {}", code, percent, message);
+    ///     Ok(())
+    /// };
+    /// let options = ProgressOptions::<Source>::default().status_callback(&mut report);
+    /// ```
+    pub fn status_callback(mut self, callback: &'a mut StatusCallback<'a>) -> Self {
+        self.status_callback = Some(callback);
+        self
+    }
 }
 
 impl<'a, T: 'static> Default for ProgressOptions<'a, T> {
     fn default() -> Self {
-        ProgressOptions { callback: None }
+        ProgressOptions {
+            callback: None,
+            async_callback: None,
+            status_callback: None,
+        }
     }
 }
@@ -0,0 +1,184 @@
+//! An anomaly detector, built using isolation forests.
+//!
+//! https://bigml.com/api/anomalies
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::model::Predicate;
+use super::source::Field;
+use super::status::*;
+use super::{Dataset, Resource, ResourceCommon};
+
+/// An anomaly detector. To compute anomaly scores without calling the BigML
+/// API, see `bigml::local::LocalAnomaly`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[api_name = "anomaly"]
+#[non_exhaustive]
+pub struct Anomaly {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Anomaly>,
+
+    /// The current status of this anomaly detector.
+    pub status: GenericStatus,
+
+    /// The number of training instances used to build each tree.
+    #[serde(default)]
+    pub sample_size: Option<u64>,
+
+    /// The structure of this anomaly detector. Will be missing or incomplete
+    /// while the anomaly detector is being created.
+    #[serde(default)]
+    pub model: Option<AnomalyInfo>,
+}
+
+/// The structure of an anomaly detector.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AnomalyInfo {
+    /// Metadata describing each field used by this anomaly detector. Keyed by
+    /// BigML field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+
+    /// The average depth of the training instances in our trees.
+    #[serde(default)]
+    pub mean_depth: Option<f64>,
+
+    /// The trees in our isolation forest.
+    #[serde(default)]
+    pub trees: Vec<AnomalyTree>,
+}
+
+/// A single tree in an isolation forest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AnomalyTree {
+    /// The root of the tree.
+    pub root: AnomalyNode,
+}
+
+/// A node in an isolation tree.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AnomalyNode {
+    /// The conditions which must all be true for an input to reach this node.
+    pub predicates: Vec<Predicate>,
+
+    /// The number of training instances which reached this node.
+    pub population: u64,
+
+    /// The child nodes of this node, if any.
+    #[serde(default)]
+    pub children: Vec<AnomalyNode>,
+}
+
+/// Arguments used to create an anomaly detector.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Anomaly")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the BigML `Dataset` to use as training data.
+    pub dataset: Id<Dataset>,
+
+    /// The IDs of the fields to use. Defaults to all fields except
+    /// `id_fields` and `excluded_fields`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub input_fields: Vec<String>,
+
+    /// The IDs of fields to leave out of training.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_fields: Vec<String>,
+
+    /// The IDs of fields which identify rows, such as customer IDs. These are
+    /// reported alongside the top anomalies, but not used for training.
+    /// Training on them would make every row look unique.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub id_fields: Vec<String>,
+
+    /// A seed which makes training deterministic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly_seed: Option<String>,
+
+    /// The number of trees in the isolation forest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forest_size: Option<u32>,
+
+    /// The number of top anomalies to report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_n: Option<u32>,
+
+    /// The value used in place of missing numeric values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_numeric_value: Option<DefaultNumericValue>,
+
+    /// The name of this anomaly detector.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args`.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            input_fields: vec![],
+            excluded_fields: vec![],
+            id_fields: vec![],
+            anomaly_seed: None,
+            forest_size: None,
+            top_n: None,
+            default_numeric_value: None,
+            name: None,
+            tags: vec![],
+        }
+    }
+}
+
+/// How to replace missing numeric values when training.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum DefaultNumericValue {
+    /// Use the mean of the field.
+    Mean,
+    /// Use the median of the field.
+    Median,
+    /// Use the minimum value of the field.
+    Minimum,
+    /// Use the maximum value of the field.
+    Maximum,
+    /// Use zero.
+    Zero,
+}
+
+#[test]
+fn args_with_training_constraints() {
+    use serde_json::json;
+    let dataset: Id<Dataset> = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let args = Args::from_dataset(dataset)
+        .with_id_fields(vec!["000000".to_owned()])
+        .with_anomaly_seed("seed")
+        .with_default_numeric_value(DefaultNumericValue::Median);
+    assert_eq!(
+        json!(args),
+        json!({
+            "dataset": "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+            "id_fields": ["000000"],
+            "anomaly_seed": "seed",
+            "default_numeric_value": "median",
+        }),
+    );
+}
@@ -0,0 +1,214 @@
+//! Resources whose type is only known at runtime.
+
+use serde::{self, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use super::id::*;
+use super::status::*;
+#[cfg(feature = "anomalies")]
+use super::Anomaly;
+#[cfg(feature = "topicmodels")]
+use super::TopicModel;
+#[cfg(feature = "clustering")]
+use super::{BatchCentroid, Cluster};
+#[cfg(feature = "models")]
+use super::{BatchPrediction, Ensemble, Evaluation, Model, Prediction};
+use super::{Dataset, Resource, ResourceCommon, Source};
+#[cfg(feature = "whizzml")]
+use super::{Execution, Library, Script};
+#[cfg(feature = "timeseries")]
+use super::{Forecast, TimeSeries};
+
+/// Declare `AnyResource`, with one variant for each resource type we support.
+/// Variants may be marked with `#[cfg(...)]` after their doc comments, which
+/// is applied everywhere the variant is mentioned.
+macro_rules! any_resource {
+    (
+        $(
+            $(#[doc = $doc:literal])*
+            $(#[cfg($cfg:meta)])?
+            $variant:ident($ty:ty),
+        )*
+    ) => {
+        /// A BigML resource of any type supported by this crate. This is
+        /// returned by `Client::fetch_any`, for tools which don't know which
+        /// kind of resource they're working with until runtime.
+        #[derive(Clone, Debug, Serialize)]
+        #[serde(untagged)]
+        #[non_exhaustive]
+        #[allow(clippy::large_enum_variant)]
+        pub enum AnyResource {
+            $(
+                $(#[doc = $doc])*
+                $(#[cfg($cfg)])?
+                $variant($ty),
+            )*
+        }
+
+        impl AnyResource {
+            /// Is `resource_type` one of the types we can represent, such as
+            /// `"dataset"`?
+            pub fn supports(resource_type: &str) -> bool {
+                $(
+                    $(#[cfg($cfg)])?
+                    if resource_type == type_name::<$ty>() {
+                        return true;
+                    }
+                )*
+                false
+            }
+
+            /// The ID of this resource.
+            pub fn id(&self) -> AnyId {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        AnyResource::$variant(r) => r.id().clone().into(),
+                    )*
+                }
+            }
+
+            /// Fields shared between all resource types.
+            pub fn common(&self) -> &ResourceCommon {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        AnyResource::$variant(r) => r.common(),
+                    )*
+                }
+            }
+
+            /// The status of this resource.
+            pub fn status(&self) -> &dyn Status {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])?
+                        AnyResource::$variant(r) => r.status(),
+                    )*
+                }
+            }
+
+            /// Convert `value` to the type named by `resource_type`.
+            fn from_value(
+                resource_type: &str,
+                value: Value,
+            ) -> serde_json::Result<Self> {
+                $(
+                    $(#[cfg($cfg)])?
+                    if resource_type == type_name::<$ty>() {
+                        let resource = serde_json::from_value(value)?;
+                        return Ok(AnyResource::$variant(resource));
+                    }
+                )*
+                Err(serde::de::Error::custom(format!(
+                    "unsupported BigML resource type {:?}",
+                    resource_type,
+                )))
+            }
+        }
+    };
+}
+
+any_resource! {
+    /// An anomaly detector.
+    #[cfg(feature = "anomalies")]
+    Anomaly(Anomaly),
+    /// A batch centroid.
+    #[cfg(feature = "clustering")]
+    BatchCentroid(BatchCentroid),
+    /// A batch prediction.
+    #[cfg(feature = "models")]
+    BatchPrediction(BatchPrediction),
+    /// A cluster.
+    #[cfg(feature = "clustering")]
+    Cluster(Cluster),
+    /// A dataset.
+    Dataset(Dataset),
+    /// An ensemble.
+    #[cfg(feature = "models")]
+    Ensemble(Ensemble),
+    /// An evaluation. We don't know what kind of model was evaluated, so the
+    /// result is left as JSON.
+    #[cfg(feature = "models")]
+    Evaluation(Evaluation<Value>),
+    /// A WhizzML execution.
+    #[cfg(feature = "whizzml")]
+    Execution(Execution),
+    /// A forecast.
+    #[cfg(feature = "timeseries")]
+    Forecast(Forecast),
+    /// A WhizzML library.
+    #[cfg(feature = "whizzml")]
+    Library(Library),
+    /// A decision tree model.
+    #[cfg(feature = "models")]
+    Model(Model),
+    /// A single prediction.
+    #[cfg(feature = "models")]
+    Prediction(Prediction),
+    /// A WhizzML script.
+    #[cfg(feature = "whizzml")]
+    Script(Script),
+    /// A source.
+    Source(Source),
+    /// A time series.
+    #[cfg(feature = "timeseries")]
+    TimeSeries(TimeSeries),
+    /// A topic model.
+    #[cfg(feature = "topicmodels")]
+    TopicModel(TopicModel),
+}
+
+/// The resource type of `R`, as it appears in IDs, such as `"dataset"`.
+fn type_name<R: Resource>() -> &'static str {
+    R::resource_type().api_name()
+}
+
+impl<'de> Deserialize<'de> for AnyResource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Just enough of a resource to tell what type it is.
+        #[derive(Deserialize)]
+        struct TypeOnly {
+            resource: AnyId,
+        }
+
+        let value = Value::deserialize(deserializer)?;
+        let id = TypeOnly::deserialize(&value)
+            .map_err(serde::de::Error::custom)?
+            .resource;
+        AnyResource::from_value(id.resource_type(), value)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[test]
+#[cfg(feature = "timeseries")]
+fn deserialize_any_resource() {
+    use serde_json::json;
+
+    let resource: AnyResource = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "sales",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "timeseries/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The time series has been created" },
+    }))
+    .unwrap();
+    assert!(matches!(resource, AnyResource::TimeSeries(_)));
+    assert_eq!(
+        resource.id().as_str(),
+        "timeseries/5e5e5e5e5e5e5e5e5e5e5e5e"
+    );
+    assert_eq!(resource.common().name, "sales");
+
+    assert!(AnyResource::supports("dataset"));
+    assert!(!AnyResource::supports("deepnet"));
+    assert!(serde_json::from_value::<AnyResource>(json!({
+        "resource": "deepnet/5e5e5e5e5e5e5e5e5e5e5e5e",
+    }))
+    .is_err());
+}
@@ -3,8 +3,9 @@
 use serde::{Deserialize, Serialize};
 
 use super::id::*;
+use super::operating_point::*;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Model, Resource, ResourceCommon};
 
 /// A batch prediction generated by BigML.
 ///
@@ -27,8 +28,142 @@ pub struct BatchPrediction {
     /// Does this prediction include all the fields in the input?
     pub all_fields: bool,
 
-    // Our output dataset.
-    //pub output_dataset_resource: Option<Id<Dataset>>,
+    /// Our output dataset, if we were asked to create one.
+    #[serde(default)]
+    pub output_dataset_resource: Option<Id<Dataset>>,
+
     /// Is our output dataset currently available?
     pub output_dataset_status: bool,
+
+    /// The supervised model used to make these predictions. This is usually
+    /// a `Model`, but may be another kind of model, such as an ensemble.
+    #[serde(default)]
+    pub model: Option<AnyId>,
+
+    /// The dataset containing the inputs.
+    #[serde(default)]
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The number of rows predicted.
+    #[serde(default)]
+    pub rows: Option<u64>,
+
+    /// The settings used to make these predictions, as reported by BigML.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_prediction: Option<serde_json::Value>,
+}
+
+impl BatchPrediction {
+    /// The output dataset, if we asked for one and it's available.
+    pub fn output_dataset(&self) -> Option<&Id<Dataset>> {
+        self.output_dataset_resource
+            .as_ref()
+            .filter(|_| self.output_dataset_status)
+    }
+}
+
+/// Arguments used to create a batch prediction.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "BatchPrediction")]
+#[non_exhaustive]
+pub struct Args {
+    /// The model used to make predictions.
+    pub model: Id<Model>,
+
+    /// The dataset containing the inputs to predict.
+    pub dataset: Id<Dataset>,
+
+    /// Should we include all the input fields in the output?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_fields: Option<bool>,
+
+    /// Should we create an output dataset, in addition to a CSV file?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dataset: Option<bool>,
+
+    /// Predict a specific class once its probability, confidence or votes
+    /// reach a threshold. Only used for classification models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operating_point: Option<OperatingPoint>,
+
+    /// Choose classes using this measure, instead of the default for the
+    /// model. Ignored if `operating_point` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operating_kind: Option<OperatingKind>,
+
+    /// The name of this batch prediction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` which uses `model` to make predictions for
+    /// `dataset`.
+    pub fn new(model: Id<Model>, dataset: Id<Dataset>) -> Args {
+        Args {
+            model,
+            dataset,
+            all_fields: None,
+            operating_point: None,
+            operating_kind: None,
+            output_dataset: None,
+            name: None,
+            tags: vec![],
+        }
+    }
+}
+
+/// What `Client::batch_predict` should return.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Output {
+    /// Download the predictions as CSV.
+    Csv,
+    /// Create an output dataset, and return its ID.
+    Dataset,
+}
+
+/// The predictions returned by `Client::batch_predict`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Predictions {
+    /// A CSV download of the predictions.
+    Csv(reqwest::Response),
+    /// A finished dataset containing the predictions.
+    Dataset(Id<Dataset>),
+}
+
+#[test]
+fn deserialize_batch_prediction() {
+    use serde_json::json;
+
+    let batch: BatchPrediction = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "scores",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "batchprediction/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The batch prediction has been created" },
+        "all_fields": false,
+        "output_dataset_resource": "dataset/5e5e5e5e5e5e5e5e5e5e5e5f",
+        "output_dataset_status": true,
+        "model": "ensemble/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "dataset": "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "rows": 150,
+        "batch_prediction": { "output_fields": [] }
+    }))
+    .unwrap();
+    assert_eq!(
+        batch.output_dataset().unwrap().as_str(),
+        "dataset/5e5e5e5e5e5e5e5e5e5e5e5f"
+    );
+    assert_eq!(batch.model.unwrap().resource_type(), "ensemble");
+    assert_eq!(batch.rows, Some(150));
 }
@@ -0,0 +1,109 @@
+//! Industry categories used to classify resources.
+
+use serde::{Deserialize, Serialize};
+
+/// Declare `Category`, mapping each variant to BigML's numeric code.
+macro_rules! categories {
+    ( $( $(#[$meta:meta])* $variant:ident = $code:expr, )* ) => {
+        /// The industry or category which best describes a resource. BigML
+        /// represents these as numbers, which we map to and from this type.
+        ///
+        /// See https://bigml.com/api/sources#s_categories.
+        #[derive(
+            Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize,
+        )]
+        #[serde(from = "i64", into = "i64")]
+        #[non_exhaustive]
+        pub enum Category {
+            $(
+                $(#[$meta])*
+                $variant,
+            )*
+            /// A category code which this library doesn't know about.
+            Other(i64),
+        }
+
+        impl From<i64> for Category {
+            fn from(code: i64) -> Self {
+                match code {
+                    $( $code => Category::$variant, )*
+                    other => Category::Other(other),
+                }
+            }
+        }
+
+        impl From<Category> for i64 {
+            fn from(category: Category) -> Self {
+                match category {
+                    $( Category::$variant => $code, )*
+                    Category::Other(code) => code,
+                }
+            }
+        }
+    };
+}
+
+categories! {
+    /// No category has been chosen.
+    Uncategorized = -1,
+    /// The default category.
+    #[default]
+    Miscellaneous = 0,
+    /// Automotive, engineering and manufacturing.
+    AutomotiveEngineeringManufacturing = 1,
+    /// Energy, oil and gas.
+    EnergyOilGas = 2,
+    /// Banking and finance.
+    BankingFinance = 3,
+    /// Fraud and crime.
+    FraudCrime = 4,
+    /// Healthcare.
+    Healthcare = 5,
+    /// Physical, earth and life sciences.
+    PhysicalEarthLifeSciences = 6,
+    /// Consumer and retail.
+    ConsumerRetail = 7,
+    /// Sports and games.
+    SportsGames = 8,
+    /// Demographics and surveys.
+    DemographicsSurveys = 9,
+    /// Aerospace and defense.
+    AerospaceDefense = 10,
+    /// Chemical and pharmaceutical.
+    ChemicalPharmaceutical = 11,
+    /// Higher education and scientific research.
+    HigherEducationScientificResearch = 12,
+    /// Human resources and psychology.
+    HumanResourcesPsychology = 13,
+    /// Insurance.
+    Insurance = 14,
+    /// Law and order.
+    LawOrder = 15,
+    /// Media, marketing and advertising.
+    MediaMarketingAdvertising = 16,
+    /// Public sector and nonprofit.
+    PublicSectorNonprofit = 17,
+    /// Professional services.
+    ProfessionalServices = 18,
+    /// Technology and communications.
+    TechnologyCommunications = 19,
+    /// Transportation and logistics.
+    TransportationLogistics = 20,
+    /// Travel and leisure.
+    TravelLeisure = 21,
+    /// Utilities.
+    Utilities = 22,
+}
+
+#[test]
+fn categories_round_trip_through_codes() {
+    use serde_json::json;
+
+    let category: Category = serde_json::from_value(json!(14)).unwrap();
+    assert_eq!(category, Category::Insurance);
+    assert_eq!(serde_json::to_value(category).unwrap(), json!(14));
+
+    let unknown: Category = serde_json::from_value(json!(99)).unwrap();
+    assert_eq!(unknown, Category::Other(99));
+    assert_eq!(serde_json::to_value(unknown).unwrap(), json!(99));
+}
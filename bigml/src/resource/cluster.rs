@@ -1,8 +1,11 @@
 //! https://bigml.com/api/clusters
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 
 use super::id::*;
+use super::source::Field;
 use super::status::*;
 use super::{Resource, ResourceCommon};
 
@@ -25,7 +28,97 @@ pub struct Cluster {
     pub status: GenericStatus,
 
     /// Details about the clusters that BigML found.
-    ///
-    /// TODO: Convert to a strongly-typed struct.
-    pub clusters: Option<serde_json::Value>,
+    pub clusters: Option<ClusterInfo>,
+}
+
+/// Details about the clusters that BigML found.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ClusterInfo {
+    /// The centroid of each cluster.
+    #[serde(default)]
+    pub clusters: Vec<Centroid>,
+
+    /// Metadata describing each field used for clustering. Keyed by BigML
+    /// field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+}
+
+/// The center of a single cluster.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Centroid {
+    /// The ID of this centroid within the cluster, such as `"000000"`.
+    pub id: String,
+
+    /// The name of this centroid, such as `"Cluster 0"`.
+    pub name: String,
+
+    /// The value of each field at the center of this cluster, keyed by BigML
+    /// field ID. Numeric fields will have numeric values, and categorical
+    /// fields will have strings.
+    pub center: HashMap<String, Value>,
+
+    /// The number of training instances in this cluster.
+    pub count: u64,
+
+    /// Statistics about the distance between training instances and this
+    /// centroid.
+    #[serde(default)]
+    pub distance: Option<DistanceStats>,
+}
+
+/// Statistics about the distance of instances from a `Centroid`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DistanceStats {
+    /// The number of instances.
+    pub population: u64,
+    /// The smallest distance.
+    pub minimum: Option<f64>,
+    /// The largest distance.
+    pub maximum: Option<f64>,
+    /// The mean distance.
+    pub mean: Option<f64>,
+    /// The median distance.
+    pub median: Option<f64>,
+    /// The standard deviation of the distances.
+    pub standard_deviation: Option<f64>,
+    /// The variance of the distances.
+    pub variance: Option<f64>,
+}
+
+#[test]
+fn deserialize_centroids() {
+    use serde_json::json;
+
+    let info: ClusterInfo = serde_json::from_value(json!({
+        "clusters": [{
+            "center": { "000000": 5.2, "000001": "red" },
+            "count": 42,
+            "distance": {
+                "bins": [[0.1, 10], [0.2, 32]],
+                "maximum": 0.3,
+                "mean": 0.18,
+                "median": 0.19,
+                "minimum": 0.05,
+                "population": 42,
+                "standard_deviation": 0.04,
+                "sum": 7.56,
+                "variance": 0.0016
+            },
+            "id": "000000",
+            "name": "Cluster 0"
+        }],
+        "fields": {
+            "000000": { "name": "size", "optype": "numeric" },
+            "000001": { "name": "color", "optype": "categorical" }
+        }
+    }))
+    .unwrap();
+    let centroid = &info.clusters[0];
+    assert_eq!(centroid.name, "Cluster 0");
+    assert_eq!(centroid.center["000001"], json!("red"));
+    assert_eq!(centroid.distance.as_ref().unwrap().mean, Some(0.18));
 }
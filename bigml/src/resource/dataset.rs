@@ -23,10 +23,11 @@ pub struct Dataset {
     /// The ID of this resource.
     pub resource: Id<Dataset>,
 
-    /// The current status of this execution.
-    pub status: GenericStatus,
+    /// The current status of this dataset.
+    pub status: DatasetStatus,
 
     /// The number of columns in the dataset.
+    #[filterable]
     pub columns: usize,
 
     /// Field IDs excluded when building this dataset.
@@ -45,16 +46,78 @@ pub struct Dataset {
     pub input_fields: Vec<String>,
 
     /// The number of rows in this dataset.
+    #[filterable]
     pub rows: usize,
 }
 
+/// The status of a `Dataset`, including information about rows and values
+/// which BigML couldn't import.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DatasetStatus {
+    /// The status fields shared by all resources. These will be serialized
+    /// at the top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: GenericStatus,
+
+    /// The number of bytes of the source which have been processed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+
+    /// The number of rows imported so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serialized_rows: Option<u64>,
+
+    /// Values which couldn't be parsed, keyed by field ID.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub field_errors: HashMap<String, ImportErrors>,
+
+    /// Rows which couldn't be parsed, perhaps because they had the wrong
+    /// number of columns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_format_errors: Option<ImportErrors>,
+}
+
+impl DatasetStatus {
+    /// The total number of values which couldn't be parsed, across all
+    /// fields.
+    pub fn field_error_count(&self) -> u64 {
+        self.field_errors.values().map(|errors| errors.total).sum()
+    }
+}
+
+status_with_common_fields!(DatasetStatus);
+
+/// A summary of the problems BigML found while importing a source or dataset.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ImportErrors {
+    /// The total number of problems.
+    #[serde(default)]
+    pub total: u64,
+
+    /// A sample of the values or rows which caused problems.
+    #[serde(default)]
+    pub sample: Vec<serde_json::Value>,
+}
+
 /// Arguments used to create a dataset.
-#[derive(Debug, Serialize)]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Dataset")]
 #[non_exhaustive]
 pub struct Args {
     /// The ID of the BigML `Source` from which to import data.
     pub source: Id<Source>,
 
+    /// The IDs of the source fields to include. Defaults to all fields.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub input_fields: Vec<String>,
+
+    /// The IDs of source fields to leave out, such as columns containing
+    /// personal information which shouldn't end up in models.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_fields: Vec<String>,
+
     /// The name of this dataset.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -69,12 +132,62 @@ impl Args {
     pub fn from_source(source: Id<Source>) -> Args {
         Args {
             source,
+            input_fields: vec![],
+            excluded_fields: vec![],
             name: None,
             tags: vec![],
         }
     }
 }
 
-impl super::Args for Args {
-    type Resource = Dataset;
+#[test]
+fn args_setters() {
+    use serde_json::json;
+    let source: Id<Source> = "source/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let args = Args::from_source(source)
+        .with_name("example")
+        .with_tags(vec!["a".to_owned()]);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        json!({ "source": "source/5e5e5e5e5e5e5e5e5e5e5e5e", "name": "example", "tags": ["a"] }),
+    );
+}
+
+#[test]
+fn args_with_excluded_fields() {
+    use serde_json::json;
+    let source: Id<Source> = "source/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let args =
+        Args::from_source(source).with_excluded_fields(vec!["000002".to_owned()]);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        json!({
+            "source": "source/5e5e5e5e5e5e5e5e5e5e5e5e",
+            "excluded_fields": ["000002"],
+        }),
+    );
+}
+
+#[test]
+fn deserialize_dataset_status() {
+    use serde_json::json;
+
+    let status: DatasetStatus = serde_json::from_value(json!({
+        "code": 5,
+        "message": "The dataset has been created",
+        "elapsed": 1200,
+        "bytes": 4608,
+        "serialized_rows": 148,
+        "field_errors": {
+            "000001": { "total": 2, "sample": ["n/a", "?"] },
+            "000003": { "total": 1, "sample": ["x"] },
+        },
+        "row_format_errors": { "total": 0, "sample": [] },
+    }))
+    .unwrap();
+    assert_eq!(status.code(), StatusCode::Finished);
+    assert_eq!(status.serialized_rows, Some(148));
+    assert_eq!(status.field_errors["000001"].sample.len(), 2);
+    assert_eq!(status.field_error_count(), 3);
+    assert_eq!(status.row_format_errors.unwrap().total, 0);
 }
@@ -4,8 +4,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::id::*;
+use super::model::{field_importance, FieldImportance};
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Resource, ResourceCommon};
 
 /// An ensemble of multiple predictive models.
 ///
@@ -37,10 +38,24 @@ pub struct Ensemble {
     /// TODO: This may need to be wrapped in `Option` to handle the early
     /// stages of resource creation, when not all fields are present.
     pub importance: HashMap<String, f64>,
+
+    /// Boosting options, if this ensemble uses gradient-boosted trees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boosting: Option<Boosting>,
     // The dataset used to create this ensemble.
     //pub dataset: Id<Dataset>,
 }
 
+impl Ensemble {
+    /// The average importance of each field, most important first, with
+    /// names from `ensemble.fields`.
+    pub fn field_importance(&self) -> Vec<FieldImportance> {
+        field_importance(self.importance.iter().map(|(id, imp)| (id, *imp)), |id| {
+            self.ensemble.fields.get(id).map(|field| field.name.clone())
+        })
+    }
+}
+
 /// Information about this ensemble.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
@@ -56,3 +71,145 @@ pub struct EnsembleField {
     /// The original name of this field (not the BigML field ID).
     pub name: String,
 }
+
+/// Options for building an ensemble of gradient-boosted trees. Pass these to
+/// `Args::boosting` to use boosting instead of bagging or random decision
+/// forests.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Boosting {
+    /// The maximum number of boosting iterations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u32>,
+
+    /// How much each new tree contributes, between 0.0 and 1.0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learning_rate: Option<f64>,
+
+    /// Should we stop early once out-of-bag samples stop improving?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub early_out_of_bag: Option<bool>,
+
+    /// The fraction of the dataset to hold out for deciding when to stop
+    /// early.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub early_holdout: Option<f64>,
+}
+
+impl Boosting {
+    /// Use BigML's default boosting options.
+    pub fn new() -> Boosting {
+        Boosting::default()
+    }
+
+    /// Set the maximum number of boosting iterations.
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// Set how much each new tree contributes.
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = Some(learning_rate);
+        self
+    }
+
+    /// Stop early once out-of-bag samples stop improving.
+    pub fn early_out_of_bag(mut self, enabled: bool) -> Self {
+        self.early_out_of_bag = Some(enabled);
+        self
+    }
+
+    /// Hold out `fraction` of the dataset for deciding when to stop early.
+    pub fn early_holdout(mut self, fraction: f64) -> Self {
+        self.early_holdout = Some(fraction);
+        self
+    }
+}
+
+/// Arguments used to create an ensemble.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Ensemble")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the BigML `Dataset` to use as training data.
+    pub dataset: Id<Dataset>,
+
+    /// The ID of the field to predict. Defaults to the last field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objective_field: Option<String>,
+
+    /// The number of models to build. With boosting, this is the number of
+    /// models built per iteration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_models: Option<u32>,
+
+    /// Build gradient-boosted trees using these options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boosting: Option<Boosting>,
+
+    /// The name of this ensemble.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args`.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            objective_field: None,
+            number_of_models: None,
+            boosting: None,
+            name: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn ensemble_field_importance_has_names() {
+    use serde_json::json;
+    let ensemble: Ensemble = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "churn",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "ensemble/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The ensemble has been created" },
+        "ensemble": {
+            "fields": {
+                "000000": { "name": "age" },
+                "000001": { "name": "plan" },
+            }
+        },
+        "importance": { "000000": 0.25, "000001": 0.75 },
+    }))
+    .unwrap();
+    let importance = ensemble.field_importance();
+    assert_eq!(importance[0].field_id, "000001");
+    assert_eq!(importance[0].name.as_deref(), Some("plan"));
+    assert_eq!(importance[1].name.as_deref(), Some("age"));
+}
+
+#[test]
+fn args_with_boosting() {
+    use serde_json::json;
+    let dataset: Id<Dataset> = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let args = Args::from_dataset(dataset)
+        .with_boosting(Boosting::new().iterations(50).learning_rate(0.1));
+    assert_eq!(
+        json!(args),
+        json!({
+            "dataset": "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+            "boosting": { "iterations": 50, "learning_rate": 0.1 },
+        }),
+    );
+}
@@ -1,12 +1,13 @@
 //! An evaluation of how well a model (or ensemble) predicts the data.
 
 use serde::de::DeserializeOwned;
-use serde::{Deserialize, Serialize};
-use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, marker::PhantomData, result};
 
 use super::id::*;
+use super::operating_point::*;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Model, Resource, ResourceCommon};
 
 /// An evaluation of how well a model (or ensemble) predicts the data.
 ///
@@ -31,6 +32,58 @@ pub struct Evaluation<R: Result> {
     pub result: R,
 }
 
+/// Arguments used to create an evaluation of a model, producing a result of
+/// type `R`.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Evaluation<R>")]
+#[non_exhaustive]
+pub struct Args<R: Result> {
+    /// The model to evaluate.
+    pub model: Id<Model>,
+
+    /// The dataset to evaluate it against. This should not be the dataset used
+    /// to train the model.
+    pub dataset: Id<Dataset>,
+
+    /// Predict a specific class once its probability, confidence or votes
+    /// reach a threshold. Only used for classification models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operating_point: Option<OperatingPoint>,
+
+    /// Choose classes using this measure, instead of the default for the
+    /// model. Ignored if `operating_point` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operating_kind: Option<OperatingKind>,
+
+    /// The name of this evaluation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Placeholder to allow us to have a type parameter.
+    #[args(skip)]
+    #[serde(skip)]
+    _phantom: PhantomData<R>,
+}
+
+impl<R: Result> Args<R> {
+    /// Create a new `Args` which evaluates `model` against `dataset`.
+    pub fn new(model: Id<Model>, dataset: Id<Dataset>) -> Args<R> {
+        Args {
+            model,
+            dataset,
+            operating_point: None,
+            operating_kind: None,
+            name: None,
+            tags: vec![],
+            _phantom: PhantomData,
+        }
+    }
+}
+
 /// The result of an evaluation.
 ///
 /// TODO: I'm not sure we want to shadow `Result`.  But this name will
@@ -41,7 +94,7 @@ pub trait Result:
 }
 
 /// The result of evaluating a classifier.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Serialize)]
 #[non_exhaustive]
 pub struct ClassificationResult {
     /// The names of our classifications.
@@ -61,8 +114,40 @@ pub struct ClassificationResult {
 
 impl Result for ClassificationResult {}
 
+/// Use raw JSON when we don't know what kind of result to expect.
+impl Result for serde_json::Value {}
+
+impl<'de> Deserialize<'de> for ClassificationResult {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> result::Result<Self, D::Error> {
+        /// The JSON fields of a `ClassificationResult`.
+        #[derive(Deserialize)]
+        struct Raw {
+            class_names: Vec<String>,
+            mode: RawDetailedClassificationResult,
+            model: RawDetailedClassificationResult,
+            random: RawDetailedClassificationResult,
+        }
+
+        // Our confusion matrices need to know the class names, which are
+        // stored once at this level.
+        let raw = Raw::deserialize(deserializer)?;
+        Ok(ClassificationResult {
+            mode: raw.mode.with_class_names(raw.class_names.clone())?,
+            model: raw.model.with_class_names(raw.class_names.clone())?,
+            random: raw.random.with_class_names(raw.class_names.clone())?,
+            class_names: raw.class_names,
+        })
+    }
+}
+
 /// The detailed result of an evaluation using specific criteria.
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// When this is deserialized on its own, the class names for
+/// `confusion_matrix` are taken from `per_class_statistics`. As part of a
+/// `ClassificationResult`, they're taken from `class_names` instead.
+#[derive(Debug, Serialize)]
 #[non_exhaustive]
 pub struct DetailedClassificationResult {
     /// The portion of instances we classified correctly.
@@ -75,12 +160,142 @@ pub struct DetailedClassificationResult {
     pub average_precision: f64,
     /// The average recall over all classes.
     pub average_recall: f64,
-    /// A list of rows of the confusion matrix for this model.
-    pub confusion_matrix: Vec<Vec<f64>>,
+    /// The confusion matrix for this model.
+    pub confusion_matrix: ConfusionMatrix,
     /// Statistics for each of the individidual classes.
     pub per_class_statistics: Vec<ClassificationPerClassStatistics>,
 }
 
+/// A confusion matrix, counting how many instances of each actual class were
+/// predicted as each class.
+///
+/// When serialized, this is represented as a list of rows, like BigML does.
+/// That list doesn't include the class names, so this can only be
+/// deserialized as part of a `DetailedClassificationResult`, which checks
+/// that there's one row and one column for each class.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfusionMatrix {
+    /// The class names used for both rows and columns.
+    class_names: Vec<String>,
+    /// One row per actual class, with one column per predicted class.
+    counts: Vec<Vec<f64>>,
+}
+
+impl ConfusionMatrix {
+    /// Create a new confusion matrix. `counts` contains one row for each
+    /// actual class, and one column for each predicted class, in the same
+    /// order as `class_names`.
+    pub fn new(class_names: Vec<String>, counts: Vec<Vec<f64>>) -> Self {
+        ConfusionMatrix {
+            class_names,
+            counts,
+        }
+    }
+
+    /// The class names used for both rows and columns.
+    pub fn class_names(&self) -> &[String] {
+        &self.class_names
+    }
+
+    /// The raw counts, with one row for each actual class.
+    pub fn rows(&self) -> &[Vec<f64>] {
+        &self.counts
+    }
+
+    /// The number of instances of class `actual` which were predicted as
+    /// class `predicted`.
+    pub fn count(&self, actual: &str, predicted: &str) -> Option<f64> {
+        let row = self.class_index(actual)?;
+        let column = self.class_index(predicted)?;
+        self.counts.get(row)?.get(column).copied()
+    }
+
+    /// The number of instances which actually belonged to `class`.
+    pub fn actual_total(&self, class: &str) -> Option<f64> {
+        let row = self.class_index(class)?;
+        Some(self.counts.get(row)?.iter().sum())
+    }
+
+    /// The number of instances which were predicted to belong to `class`.
+    pub fn predicted_total(&self, class: &str) -> Option<f64> {
+        let column = self.class_index(class)?;
+        self.counts.iter().map(|row| row.get(column).copied()).sum()
+    }
+
+    /// The total number of instances.
+    pub fn total(&self) -> f64 {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// The position of `class` in our rows and columns.
+    fn class_index(&self, class: &str) -> Option<usize> {
+        self.class_names.iter().position(|name| name == class)
+    }
+}
+
+impl<'de> Deserialize<'de> for DetailedClassificationResult {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> result::Result<Self, D::Error> {
+        let raw = RawDetailedClassificationResult::deserialize(deserializer)?;
+        let class_names = raw
+            .per_class_statistics
+            .iter()
+            .map(|stats| stats.class_name.clone())
+            .collect();
+        raw.with_class_names(class_names)
+    }
+}
+
+/// The JSON fields of a `DetailedClassificationResult`, before we know the
+/// class names for its confusion matrix.
+#[derive(Deserialize)]
+struct RawDetailedClassificationResult {
+    accuracy: f64,
+    average_f_measure: f64,
+    average_phi: f64,
+    average_precision: f64,
+    average_recall: f64,
+    confusion_matrix: Vec<Vec<f64>>,
+    per_class_statistics: Vec<ClassificationPerClassStatistics>,
+}
+
+impl RawDetailedClassificationResult {
+    /// Build a `DetailedClassificationResult`, using `class_names` for the
+    /// rows and columns of our confusion matrix.
+    fn with_class_names<E: serde::de::Error>(
+        self,
+        class_names: Vec<String>,
+    ) -> result::Result<DetailedClassificationResult, E> {
+        let size = class_names.len();
+        let counts = self.confusion_matrix;
+        if counts.len() != size || counts.iter().any(|row| row.len() != size) {
+            return Err(E::custom(format!(
+                "expected a {}x{} confusion matrix for {} classes",
+                size, size, size,
+            )));
+        }
+        Ok(DetailedClassificationResult {
+            accuracy: self.accuracy,
+            average_f_measure: self.average_f_measure,
+            average_phi: self.average_phi,
+            average_precision: self.average_precision,
+            average_recall: self.average_recall,
+            confusion_matrix: ConfusionMatrix::new(class_names, counts),
+            per_class_statistics: self.per_class_statistics,
+        })
+    }
+}
+
+impl Serialize for ConfusionMatrix {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> result::Result<S::Ok, S::Error> {
+        self.counts.serialize(serializer)
+    }
+}
+
 /// The detailed result of an evaluation using specific criteria.
 #[derive(Debug, Deserialize, Serialize)]
 #[non_exhaustive]
@@ -100,6 +315,273 @@ pub struct ClassificationPerClassStatistics {
     /// The number of true positives over the number of actual positives in
     /// the dataset. (TP / (TP + FN))
     pub recall: f64,
+    /// The area under `roc_curve`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area_under_roc_curve: Option<f64>,
+    /// The area under `precision_recall_curve`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area_under_pr_curve: Option<f64>,
+    /// The ROC curve for this class, with one point per threshold.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roc_curve: Vec<RocPoint>,
+    /// The precision-recall curve for this class, with one point per
+    /// threshold.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub precision_recall_curve: Vec<PrecisionRecallPoint>,
+    /// The cumulative gain curve for this class.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gain_curve: Vec<GainPoint>,
+    /// The lift curve for this class.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lift_curve: Vec<LiftPoint>,
+}
+
+impl ClassificationPerClassStatistics {
+    /// The threshold with the best f-measure on `precision_recall_curve`, if
+    /// we have one.
+    pub fn best_f_measure_threshold(&self) -> Option<f64> {
+        self.precision_recall_curve
+            .iter()
+            .map(|point| (point.f_measure(), point.threshold))
+            .filter(|(f_measure, _)| !f_measure.is_nan())
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, threshold)| threshold)
+    }
+
+    /// The highest threshold on `roc_curve` which has a true positive rate of
+    /// at least `min_true_positive_rate`, if any.
+    pub fn threshold_for_true_positive_rate(
+        &self,
+        min_true_positive_rate: f64,
+    ) -> Option<f64> {
+        self.roc_curve
+            .iter()
+            .filter(|point| point.true_positive_rate >= min_true_positive_rate)
+            .map(|point| point.threshold)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// Declare a point on a curve. BigML represents these as `[x, y, threshold]`
+/// arrays, so that's how we serialize them.
+macro_rules! curve_point {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(#[$x_meta:meta])* pub $x:ident,
+            $(#[$y_meta:meta])* pub $y:ident,
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[non_exhaustive]
+        pub struct $name {
+            $(#[$x_meta])*
+            pub $x: f64,
+            $(#[$y_meta])*
+            pub $y: f64,
+            /// The confidence (or probability) threshold for this point.
+            pub threshold: f64,
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> result::Result<Self, D::Error> {
+                let ($x, $y, threshold) = <(f64, f64, f64)>::deserialize(deserializer)?;
+                Ok($name { $x, $y, threshold })
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(
+                &self,
+                serializer: S,
+            ) -> result::Result<S::Ok, S::Error> {
+                (self.$x, self.$y, self.threshold).serialize(serializer)
+            }
+        }
+    };
+}
+
+curve_point! {
+    /// A point on an ROC curve.
+    pub struct RocPoint {
+        /// The fraction of negatives which were predicted as positive.
+        pub false_positive_rate,
+        /// The fraction of positives which were predicted as positive.
+        pub true_positive_rate,
+    }
+}
+
+curve_point! {
+    /// A point on a precision-recall curve.
+    pub struct PrecisionRecallPoint {
+        /// The fraction of positives which were predicted as positive.
+        pub recall,
+        /// The fraction of positive predictions which were correct.
+        pub precision,
+    }
+}
+
+impl PrecisionRecallPoint {
+    /// The harmonic mean of precision and recall at this point.
+    pub fn f_measure(&self) -> f64 {
+        2.0 * self.precision * self.recall / (self.precision + self.recall)
+    }
+}
+
+curve_point! {
+    /// A point on a cumulative gain curve.
+    pub struct GainPoint {
+        /// The fraction of all instances predicted as positive.
+        pub fraction_of_instances,
+        /// The fraction of positives found among those instances.
+        pub fraction_of_positives,
+    }
+}
+
+curve_point! {
+    /// A point on a lift curve.
+    pub struct LiftPoint {
+        /// The fraction of all instances predicted as positive.
+        pub fraction_of_instances,
+        /// How many times better than random our predictions are.
+        pub lift,
+    }
 }
 
 // TODO: RegressionResult.
+
+#[test]
+fn args_with_setters() {
+    use serde_json::json;
+    let model: Id<Model> = "model/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let dataset: Id<Dataset> = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let args = Args::<ClassificationResult>::new(model, dataset)
+        .with_operating_point(OperatingPoint::new(
+            OperatingKind::Probability,
+            "yes",
+            0.2,
+        ))
+        .with_name("holdout")
+        .with_tags(vec!["nightly".to_owned()]);
+    assert_eq!(
+        json!(args),
+        json!({
+            "model": "model/5e5e5e5e5e5e5e5e5e5e5e5e",
+            "dataset": "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+            "operating_point": {
+                "kind": "probability",
+                "positive_class": "yes",
+                "threshold": 0.2,
+            },
+            "name": "holdout",
+            "tags": ["nightly"],
+        }),
+    );
+}
+
+#[test]
+fn confusion_matrix_uses_class_names() {
+    use serde_json::json;
+
+    let detailed = json!({
+        "accuracy": 0.75,
+        "average_f_measure": 0.7,
+        "average_phi": 0.5,
+        "average_precision": 0.7,
+        "average_recall": 0.7,
+        "confusion_matrix": [[5, 1], [2, 4]],
+        "per_class_statistics": []
+    });
+    let result: ClassificationResult = serde_json::from_value(json!({
+        "class_names": ["no", "yes"],
+        "mode": detailed,
+        "model": detailed,
+        "random": detailed,
+    }))
+    .unwrap();
+    let matrix = &result.model.confusion_matrix;
+    assert_eq!(matrix.class_names(), &["no", "yes"]);
+    assert_eq!(matrix.count("no", "yes"), Some(1.0));
+    assert_eq!(matrix.count("yes", "no"), Some(2.0));
+    assert_eq!(matrix.count("maybe", "no"), None);
+    assert_eq!(matrix.actual_total("yes"), Some(6.0));
+    assert_eq!(matrix.predicted_total("yes"), Some(5.0));
+    assert_eq!(matrix.total(), 12.0);
+    assert_eq!(
+        serde_json::to_value(matrix).unwrap(),
+        json!([[5.0, 1.0], [2.0, 4.0]]),
+    );
+}
+
+#[test]
+fn detailed_results_take_class_names_from_statistics() {
+    use serde_json::json;
+
+    let stats = |class_name: &str| {
+        json!({
+            "accuracy": 0.75,
+            "class_name": class_name,
+            "f_measure": 0.7,
+            "phi_coefficient": 0.5,
+            "precision": 0.7,
+            "recall": 0.7,
+        })
+    };
+    let detailed = |per_class_statistics| {
+        json!({
+            "accuracy": 0.75,
+            "average_f_measure": 0.7,
+            "average_phi": 0.5,
+            "average_precision": 0.7,
+            "average_recall": 0.7,
+            "confusion_matrix": [[5, 1], [2, 4]],
+            "per_class_statistics": per_class_statistics,
+        })
+    };
+    let result: DetailedClassificationResult =
+        serde_json::from_value(detailed(json!([stats("no"), stats("yes")]))).unwrap();
+    let matrix = &result.confusion_matrix;
+    assert_eq!(matrix.class_names(), &["no", "yes"]);
+    assert_eq!(matrix.count("yes", "no"), Some(2.0));
+    assert_eq!(matrix.actual_total("no"), Some(6.0));
+
+    // We need one row and one column for each class.
+    let missing_class = detailed(json!([stats("no")]));
+    assert!(
+        serde_json::from_value::<DetailedClassificationResult>(missing_class).is_err()
+    );
+    let extra_class = json!({
+        "class_names": ["maybe", "no", "yes"],
+        "mode": detailed(json!([])),
+        "model": detailed(json!([])),
+        "random": detailed(json!([])),
+    });
+    assert!(serde_json::from_value::<ClassificationResult>(extra_class).is_err());
+}
+
+#[test]
+fn per_class_statistics_include_curves() {
+    use serde_json::json;
+
+    let json = json!({
+        "accuracy": 0.75,
+        "class_name": "yes",
+        "f_measure": 0.7,
+        "phi_coefficient": 0.5,
+        "precision": 0.7,
+        "recall": 0.7,
+        "area_under_roc_curve": 0.8,
+        "roc_curve": [[0.0, 0.0, 1.0], [0.1, 0.6, 0.8], [0.4, 0.9, 0.3], [1.0, 1.0, 0.0]],
+        "precision_recall_curve": [[0.6, 0.9, 0.8], [0.9, 0.7, 0.3], [1.0, 0.5, 0.0]]
+    });
+    let stats: ClassificationPerClassStatistics =
+        serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(stats.roc_curve[1].true_positive_rate, 0.6);
+    assert_eq!(stats.best_f_measure_threshold(), Some(0.3));
+    assert_eq!(stats.threshold_for_true_positive_rate(0.85), Some(0.3));
+    assert_eq!(stats.threshold_for_true_positive_rate(2.0), None);
+    assert_eq!(serde_json::to_value(&stats).unwrap(), json);
+}
@@ -8,14 +8,14 @@ use std::fmt;
 
 use super::Execution;
 use crate::errors::*;
-use crate::resource;
 use crate::resource::id::*;
 use crate::resource::Script;
 
 /// Arguments for creating a script execution.
 ///
 /// TODO: Lots of missing fields.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Args, Clone, Debug, Default, Deserialize, Serialize)]
+#[args(resource = "Execution")]
 #[non_exhaustive]
 pub struct Args {
     /// The ID of the script to run.
@@ -76,10 +76,6 @@ impl Args {
     }
 }
 
-impl resource::Args for Args {
-    type Resource = Execution;
-}
-
 /// A named output value from an execution.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
@@ -189,8 +185,9 @@ fn deserialize_output_with_name_and_value_but_no_type() {
 }
 
 #[test]
+#[cfg(feature = "models")]
 fn deserialize_output_with_everything() {
-    use resource::evaluation::{ClassificationResult, Evaluation};
+    use crate::resource::evaluation::{ClassificationResult, Evaluation};
 
     let json =
         r#"["evaluation", "evaluation/50650d563c19202679000000", "evaluation"]"#;
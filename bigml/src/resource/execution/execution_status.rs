@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fmt;
 
 use crate::resource::status::*;
 
+// `Cause` used to be defined here, and is now shared with `GenericStatus`.
+pub use crate::resource::status::Cause;
+
 /// Execution-specific status information.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
@@ -29,6 +31,10 @@ pub struct ExecutionStatus {
     )]
     pub call_stack: Option<Vec<Option<SourceLocation>>>,
 
+    /// The WhizzML error code, such as `-8200`, if something went wrong.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<i64>,
+
     /// The cause of the error.
     pub cause: Option<Cause>,
 
@@ -77,6 +83,14 @@ impl Status for ExecutionStatus {
     fn progress(&self) -> Option<f32> {
         self.progress
     }
+
+    fn error_code(&self) -> Option<i64> {
+        self.error
+    }
+
+    fn cause(&self) -> Option<&Cause> {
+        self.cause.as_ref()
+    }
 }
 
 /// Functions for (de)serializing WhizzML call stacks.
@@ -144,32 +158,6 @@ pub struct SourceLocation {
     pub origin: usize,
 }
 
-/// The cause of an error.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[non_exhaustive]
-pub struct Cause {
-    /// The error code of the underlying error.
-    pub code: i64,
-
-    /// Extra information about the underlying error (may be a list or
-    /// hash, possibly other things).
-    #[serde(default)]
-    pub extra: serde_json::Value,
-
-    /// The HTTP status related to the underlying error.
-    pub http_status: Option<u16>,
-}
-
-impl fmt::Display for Cause {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "code: {}", self.code)?;
-        if let Some(http_status) = self.http_status {
-            write!(f, ", HTTP status: {}", http_status)?;
-        }
-        write!(f, ", extra: {}", self.extra,)
-    }
-}
-
 /// Information on the instruction where an error occurred.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -188,5 +176,6 @@ fn deserialize_error_status() {
 
     let json = r#"{"call_stack": [[1, [32, 47], [15, 1]]], "cause": {"code": -1206, "extra": {"all_fields": "Must be true or false", "fields": "Must be an object"}, "http_status": 400}, "code": -1, "elapsed": 8896, "elapsed_times": {"in-progress": 8834, "queued": 22, "started": 62}, "error": -8200, "instruction": {"instruction": "apply", "source": {"columns": [15, 1], "lines": [32, 47], "origin": 1}}, "message": "Problem while executing script: Error handling resource (Validation error)", "progress": 0.195, "source_location": {"columns": [15, 1], "lines": [32, 47], "origin": 1}}"#;
     let status: ExecutionStatus = serde_json::from_str(json).unwrap();
+    assert_eq!(status.error_code(), Some(-8200));
     assert_eq!(status.cause.unwrap().code, -1206);
 }
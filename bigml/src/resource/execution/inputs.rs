@@ -0,0 +1,244 @@
+//! Type-checked inputs for script executions.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::Args;
+use crate::errors::*;
+use crate::resource::id::*;
+use crate::resource::script::{Input, Type};
+use crate::resource::{Resource, Script};
+
+/// Resource types which WhizzML accepts as a `supervised-model-id`.
+const SUPERVISED_MODEL_TYPES: &[&str] = &[
+    "deepnet",
+    "ensemble",
+    "fusion",
+    "linearregression",
+    "logisticregression",
+    "model",
+];
+
+/// Builds execution `Args` for a specific `Script`, checking each input
+/// against the names and types the script declares.
+///
+/// Mistakes are reported by `build`, so that setters can be chained.
+///
+/// ```
+/// # use bigml::resource::{execution::InputsBuilder, Dataset, Id, Script};
+/// # fn example(script: &Script, dataset: &Id<Dataset>) -> bigml::Result<()> {
+/// let args = InputsBuilder::from_script(script)
+///     .set_integer("n", 2)
+///     .set_resource("dataset", dataset)
+///     .build()?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InputsBuilder {
+    args: Args,
+    declared: Vec<Input>,
+    /// The first mistake we found, if any.
+    error: Option<Error>,
+}
+
+impl InputsBuilder {
+    /// Start building inputs for `script`.
+    pub fn from_script(script: &Script) -> InputsBuilder {
+        let mut args = Args::default();
+        args.set_script(script.resource.clone());
+        InputsBuilder {
+            args,
+            declared: script.inputs.clone(),
+            error: None,
+        }
+    }
+
+    /// Set the string input `name`.
+    pub fn set_string<S: Into<String>>(self, name: &str, value: S) -> Self {
+        self.set_checked(name, Value::from(value.into()), "a string", |ty| {
+            matches!(
+                ty,
+                Type::String | Type::Categorical | Type::Text | Type::Items
+            )
+        })
+    }
+
+    /// Set the integer input `name`.
+    pub fn set_integer(self, name: &str, value: i64) -> Self {
+        self.set_checked(name, Value::from(value), "an integer", |ty| {
+            matches!(ty, Type::Integer | Type::Number | Type::Numeric)
+        })
+    }
+
+    /// Set the numeric input `name`.
+    pub fn set_number(self, name: &str, value: f64) -> Self {
+        self.set_checked(name, Value::from(value), "a number", |ty| {
+            matches!(ty, Type::Number | Type::Numeric)
+        })
+    }
+
+    /// Set the boolean input `name`.
+    pub fn set_boolean(self, name: &str, value: bool) -> Self {
+        self.set_checked(name, Value::from(value), "a boolean", |ty| {
+            ty == Type::Boolean
+        })
+    }
+
+    /// Set the resource ID input `name`.
+    pub fn set_resource<R: Resource>(self, name: &str, id: &Id<R>) -> Self {
+        let resource_type = R::resource_type().api_name();
+        let expected = format!("a {} ID", resource_type);
+        self.set_checked(name, Value::from(id.as_str()), &expected, |ty| {
+            resource_id_type_matches(ty, resource_type)
+        })
+    }
+
+    /// Set the input `name` to an arbitrary JSON-compatible value. We check
+    /// that the script declares `name`, but not its type.
+    pub fn set_value<V: Serialize>(mut self, name: &str, value: V) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value) => self.set_checked(name, value, "any value", |_| true),
+            Err(err) => {
+                self.fail(Error::invalid_script_input(name, err.to_string()));
+                self
+            }
+        }
+    }
+
+    /// Build our execution `Args`, or report the first mistake we found. We
+    /// also check that every input without a default value has been set.
+    pub fn build(self) -> Result<Args> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        for input in &self.declared {
+            let is_set = self.args.inputs.iter().any(|(name, _)| *name == input.name);
+            if !is_set && input.default.is_none() {
+                return Err(Error::invalid_script_input(
+                    &input.name,
+                    "no value given for required input",
+                ));
+            }
+        }
+        Ok(self.args)
+    }
+
+    /// Set `name` to `value` if the script declares it with a type accepted
+    /// by `type_ok`. `expected` describes `value` for error messages.
+    fn set_checked<F>(
+        mut self,
+        name: &str,
+        value: Value,
+        expected: &str,
+        type_ok: F,
+    ) -> Self
+    where
+        F: Fn(Type) -> bool,
+    {
+        let declared = self.declared.iter().find(|input| input.name == name);
+        let result = match declared {
+            None => Err(Error::invalid_script_input(
+                name,
+                "script has no input with this name",
+            )),
+            Some(input) if !type_ok(input.type_) => Err(Error::invalid_script_input(
+                name,
+                format!("expected a value of type {}, got {}", input.type_, expected),
+            )),
+            Some(_) => self.args.add_input(name, value),
+        };
+        if let Err(err) = result {
+            self.fail(err);
+        }
+        self
+    }
+
+    /// Remember `err`, unless we've already seen an error.
+    fn fail(&mut self, err: Error) {
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
+    }
+}
+
+/// Can an ID of the type `resource_type` be passed to an input of type `ty`?
+fn resource_id_type_matches(ty: Type, resource_type: &str) -> bool {
+    match ty {
+        Type::ResourceId => true,
+        Type::SupervisedModelId => SUPERVISED_MODEL_TYPES.contains(&resource_type),
+        // Typed ID inputs are named after the resource, like `dataset-id`.
+        _ => ty.to_string() == format!("{}-id", resource_type),
+    }
+}
+
+#[test]
+fn inputs_are_checked_against_script() {
+    use serde_json::json;
+
+    use crate::resource::{Dataset, Source};
+
+    let script: Script = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "double",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "script/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The script has been created" },
+        "source_code": "(define out (* n 2))",
+        "inputs": [
+            { "name": "n", "type": "integer" },
+            { "name": "dataset", "type": "dataset-id" },
+            { "name": "label", "type": "string", "default": "x" },
+        ],
+        "outputs": [],
+    }))
+    .unwrap();
+    let dataset: Id<Dataset> = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let source: Id<Source> = "source/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+
+    let args = InputsBuilder::from_script(&script)
+        .set_integer("n", 2)
+        .set_resource("dataset", &dataset)
+        .build()
+        .unwrap();
+    assert_eq!(args.script, Some(script.resource.clone()));
+    assert_eq!(
+        args.inputs,
+        vec![
+            ("n".to_owned(), json!(2)),
+            (
+                "dataset".to_owned(),
+                json!("dataset/5e5e5e5e5e5e5e5e5e5e5e5e")
+            ),
+        ],
+    );
+
+    let wrong_type = InputsBuilder::from_script(&script)
+        .set_integer("n", 2)
+        .set_resource("dataset", &source)
+        .build();
+    assert!(matches!(
+        wrong_type,
+        Err(Error::InvalidScriptInput { ref name, .. }) if name == "dataset"
+    ));
+
+    let unknown = InputsBuilder::from_script(&script)
+        .set_string("nope", "a")
+        .build();
+    assert!(matches!(
+        unknown,
+        Err(Error::InvalidScriptInput { ref name, .. }) if name == "nope"
+    ));
+
+    let missing = InputsBuilder::from_script(&script)
+        .set_integer("n", 2)
+        .build();
+    assert!(matches!(
+        missing,
+        Err(Error::InvalidScriptInput { ref name, .. }) if name == "dataset"
+    ));
+}
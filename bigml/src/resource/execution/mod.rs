@@ -4,6 +4,7 @@ use serde::de;
 use serde::de::DeserializeOwned;
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::fmt;
 use url::Url;
 
@@ -16,9 +17,11 @@ use crate::errors::*;
 
 mod args;
 mod execution_status;
+mod inputs;
 
 pub use self::args::*;
 pub use self::execution_status::*;
+pub use self::inputs::*;
 
 /// An execution of a WhizzML script.
 ///
@@ -38,6 +41,10 @@ pub struct Execution {
     /// The current status of this execution.
     pub status: ExecutionStatus,
 
+    /// The inputs passed to this execution.
+    #[serde(default)]
+    pub inputs: Vec<(String, serde_json::Value)>,
+
     /// Further information about this execution.
     pub execution: Data,
 }
@@ -70,7 +77,8 @@ pub struct Data {
 }
 
 impl Data {
-    /// Get a named output of this execution.
+    /// Get a named output of this execution. See `require` for a version
+    /// with more helpful errors.
     pub fn get<D: DeserializeOwned>(&self, name: &str) -> Result<D> {
         for output in &self.outputs {
             if output.name == name {
@@ -82,6 +90,29 @@ impl Data {
             Error::OutputNotAvailable {},
         ))
     }
+
+    /// The outputs of this execution, keyed by name.
+    pub fn outputs_map(&self) -> HashMap<&str, &Output> {
+        self.outputs
+            .iter()
+            .map(|output| (output.name.as_str(), output))
+            .collect()
+    }
+
+    /// Get a named output of this execution. If there's no such output, the
+    /// error lists the names of the outputs we do have.
+    pub fn require<D: DeserializeOwned>(&self, name: &str) -> Result<D> {
+        match self.outputs.iter().find(|output| output.name == name) {
+            Some(output) => output.get(),
+            None => Err(Error::no_such_output(
+                name,
+                self.outputs
+                    .iter()
+                    .map(|output| output.name.clone())
+                    .collect(),
+            )),
+        }
+    }
 }
 
 /// Information about a source code resource.
@@ -231,3 +262,17 @@ impl Serialize for SourceId {
         }
     }
 }
+
+#[test]
+fn require_lists_available_outputs() {
+    let data: Data = serde_json::from_str(
+        r#"{"outputs": [["count", 3, "Integer"], ["label", "x", "String"]]}"#,
+    )
+    .unwrap();
+    assert_eq!(data.outputs_map()["label"].value, Some("x".into()));
+    assert_eq!(data.require::<u64>("count").unwrap(), 3);
+
+    let err = data.require::<u64>("total").unwrap_err();
+    assert!(matches!(err, Error::NoSuchOutput { ref name, .. } if name == "total"));
+    assert!(err.to_string().contains("available outputs: count, label"));
+}
@@ -0,0 +1,179 @@
+//! A forecast of future values, made using a time series.
+//!
+//! https://bigml.com/api/forecasts
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::status::*;
+use super::{Resource, ResourceCommon, TimeSeries};
+
+/// A forecast of future values, made using a `TimeSeries`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[api_name = "forecast"]
+#[non_exhaustive]
+pub struct Forecast {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Forecast>,
+
+    /// The current status of this forecast.
+    pub status: GenericStatus,
+
+    /// The time series used to make this forecast.
+    pub timeseries: Id<TimeSeries>,
+
+    /// The forecast itself. Will be missing while the forecast is being
+    /// created.
+    #[serde(default)]
+    pub forecast: Option<ForecastInfo>,
+}
+
+impl Forecast {
+    /// The forecasts made for `field_id`, one per model, or an empty list if
+    /// there aren't any.
+    pub fn field_results(&self, field_id: &str) -> &[FieldForecast] {
+        self.forecast
+            .as_ref()
+            .and_then(|info| info.result.get(field_id))
+            .map(|results| &results[..])
+            .unwrap_or(&[])
+    }
+}
+
+/// The results of a forecast.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ForecastInfo {
+    /// The forecasts made for each objective field, keyed by BigML field ID.
+    /// Each field may be forecast using several models.
+    #[serde(default)]
+    pub result: HashMap<String, Vec<FieldForecast>>,
+}
+
+/// A forecast for a single field, made using a single model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct FieldForecast {
+    /// The name of the exponential smoothing model used, such as `"A,N,N"`.
+    pub model: String,
+
+    /// The forecast value at each point in the horizon.
+    pub point_forecast: Vec<f64>,
+
+    /// The lower bound of the confidence interval at each point, if
+    /// available.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lower_bound: Vec<f64>,
+
+    /// The upper bound of the confidence interval at each point, if
+    /// available.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upper_bound: Vec<f64>,
+
+    /// The Akaike information criterion of the model, if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aic: Option<f64>,
+
+    /// The corrected Akaike information criterion of the model, if
+    /// available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aicc: Option<f64>,
+
+    /// The Bayesian information criterion of the model, if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bic: Option<f64>,
+
+    /// The R² of the model on the training data, if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r_squared: Option<f64>,
+}
+
+/// Arguments used to create a forecast.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Forecast")]
+#[non_exhaustive]
+pub struct Args {
+    /// The time series to use.
+    pub timeseries: Id<TimeSeries>,
+
+    /// What to forecast for each objective field, keyed by BigML field ID.
+    pub input_data: HashMap<String, ForecastInput>,
+
+    /// The name of this forecast.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args`. You'll need to call `add_horizon` for at least
+    /// one field.
+    pub fn new(timeseries: Id<TimeSeries>) -> Args {
+        Args {
+            timeseries,
+            input_data: HashMap::new(),
+            name: None,
+            tags: vec![],
+        }
+    }
+
+    /// Forecast `horizon` future values of `field_id`.
+    pub fn add_horizon<S: Into<String>>(&mut self, field_id: S, horizon: u32) {
+        self.input_data
+            .insert(field_id.into(), ForecastInput { horizon });
+    }
+}
+
+/// What to forecast for a single field.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ForecastInput {
+    /// The number of future values to forecast.
+    pub horizon: u32,
+}
+
+#[test]
+fn deserialize_forecast_results() {
+    use serde_json::json;
+
+    let forecast: Forecast = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "sales",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "forecast/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The forecast has been created" },
+        "timeseries": "timeseries/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "forecast": {
+            "result": {
+                "000005": [{
+                    "model": "A,N,N",
+                    "point_forecast": [10.5, 11.0],
+                    "lower_bound": [9.0, 9.1],
+                    "upper_bound": [12.0, 12.9],
+                    "aic": 123.4
+                }]
+            }
+        }
+    }))
+    .unwrap();
+    let results = forecast.field_results("000005");
+    assert_eq!(results[0].model, "A,N,N");
+    assert_eq!(results[0].point_forecast, vec![10.5, 11.0]);
+    assert_eq!(results[0].aic, Some(123.4));
+    assert!(forecast.field_results("000000").is_empty());
+}
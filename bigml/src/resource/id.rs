@@ -2,7 +2,11 @@
 
 use serde::de::Unexpected;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::str::FromStr;
 use url::Url;
@@ -10,9 +14,80 @@ use url::Url;
 use super::Resource;
 use crate::errors::*;
 
+/// Declare `ResourceType`, with one variant for each BigML API name. We get
+/// the list of names from `bigml_derive`, which uses it to check `api_name`.
+macro_rules! resource_types {
+    ( $( $variant:ident => $name:literal, )* ) => {
+        /// The type of a BigML resource, as used in IDs and API paths.
+        ///
+        /// This includes resource types which this crate doesn't implement
+        /// yet. Types which BigML added more recently are represented as
+        /// `ResourceType::Other`.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[non_exhaustive]
+        pub enum ResourceType {
+            $(
+                #[doc = concat!("`", $name, "` resources.")]
+                $variant,
+            )*
+            /// Any other resource type, specified by its API name.
+            Other(&'static str),
+        }
+
+        impl ResourceType {
+            /// Look up the `ResourceType` for `name`, such as `"dataset"`,
+            /// falling back to `ResourceType::Other`.
+            pub fn from_api_name(name: &'static str) -> ResourceType {
+                ResourceType::known(name).unwrap_or(ResourceType::Other(name))
+            }
+
+            /// Look up the `ResourceType` for `name`, if it's one we know
+            /// about.
+            pub fn known(name: &str) -> Option<ResourceType> {
+                match name {
+                    $( $name => Some(ResourceType::$variant), )*
+                    _ => None,
+                }
+            }
+
+            /// The name of this type in the BigML API, such as `"dataset"`.
+            pub fn api_name(self) -> &'static str {
+                match self {
+                    $( ResourceType::$variant => $name, )*
+                    ResourceType::Other(name) => name,
+                }
+            }
+        }
+    };
+}
+
+known_resource_types!(resource_types);
+
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.api_name())
+    }
+}
+
+/// Parse the API name of a resource type we know about, such as
+/// `"dataset"`. Unlike `ResourceType::from_api_name`, this never returns
+/// `ResourceType::Other`.
+impl FromStr for ResourceType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ResourceType::known(s).ok_or_else(|| Error::UnsupportedResourceType {
+            resource_type: s.to_owned(),
+        })
+    }
+}
+
 /// A strongly-typed "resource ID" used to identify many different kinds of
 /// BigML resources.
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// IDs compare, order and hash exactly like the underlying strings, so an
+/// `Id` can be compared against a `&str`, and a `HashMap<Id<R>, V>` can be
+/// looked up using a `&str`.
 pub struct Id<R: Resource> {
     /// The ID of the resource.
     id: String,
@@ -28,7 +103,15 @@ impl<R: Resource> Id<R> {
         &self.id
     }
 
-    /// Get a URL pointing at the human-readable version of this resource.
+    /// The type of this resource, as it appears in the ID, such as
+    /// `"dataset"`.
+    pub fn resource_type(&self) -> &'static str {
+        R::resource_type().api_name()
+    }
+
+    /// Get a URL pointing at the human-readable version of this resource on
+    /// `bigml.com`. If you use a private BigML deployment, use
+    /// `Client::dashboard_url` instead.
     pub fn dashboard_url(&self) -> Url {
         Url::parse(&format!("https://bigml.com/dashboard/{}", self))
             // This should never fail to parse.
@@ -36,11 +119,78 @@ impl<R: Resource> Id<R> {
     }
 }
 
+impl<R: Resource> AsRef<str> for Id<R> {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+// We implement these traits by hand, because `#[derive]` would require `R` to
+// implement them, too.
+
+impl<R: Resource> Borrow<str> for Id<R> {
+    fn borrow(&self) -> &str {
+        &self.id
+    }
+}
+
+impl<R: Resource> Clone for Id<R> {
+    fn clone(&self) -> Self {
+        Id {
+            id: self.id.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: Resource> PartialEq for Id<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<R: Resource> Eq for Id<R> {}
+
+impl<R: Resource> PartialEq<str> for Id<R> {
+    fn eq(&self, other: &str) -> bool {
+        self.id == other
+    }
+}
+
+impl<R: Resource> PartialEq<&str> for Id<R> {
+    fn eq(&self, other: &&str) -> bool {
+        self.id == *other
+    }
+}
+
+impl<R: Resource> PartialOrd for Id<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R: Resource> Ord for Id<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<R: Resource> Hash for Id<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // This must match `str`, because we implement `Borrow<str>`.
+        self.id.as_str().hash(state)
+    }
+}
+
+/// Parse an ID, checking both its type and that the rest of it looks like a
+/// real BigML ID, so that typos fail here instead of causing a 404 later.
 impl<R: Resource> FromStr for Id<R> {
     type Err = Error;
 
     fn from_str(id: &str) -> Result<Self> {
-        if id.starts_with(R::id_prefix()) {
+        let parts = IdParts::parse(id)?;
+        if R::resource_type().api_name() == parts.resource_type {
+            parts.validate()?;
             Ok(Id {
                 id: id.to_owned(),
                 _phantom: PhantomData,
@@ -54,6 +204,211 @@ impl<R: Resource> FromStr for Id<R> {
     }
 }
 
+/// The pieces of a BigML resource ID, such as `"dataset/5e5e…"`,
+/// `"shared/dataset/abcXYZ"` or `"public/dataset/5e5e…"`.
+struct IdParts<'a> {
+    /// The original ID.
+    id: &'a str,
+    /// `Some("shared")` or `Some("public")` if this ID has a visibility
+    /// prefix.
+    visibility: Option<&'a str>,
+    /// The type of this resource, such as `"dataset"`.
+    resource_type: &'a str,
+    /// Everything after the resource type.
+    suffix: &'a str,
+}
+
+impl<'a> IdParts<'a> {
+    /// Split `id` into parts, checking only its overall shape.
+    fn parse(id: &'a str) -> Result<IdParts<'a>> {
+        let (visibility, rest) = match id.split_once('/') {
+            Some((visibility @ "shared", rest))
+            | Some((visibility @ "public", rest)) => (Some(visibility), rest),
+            _ => (None, id),
+        };
+        let (resource_type, suffix) = rest
+            .split_once('/')
+            .ok_or_else(|| Error::invalid_id(id, "expected \"type/id\""))?;
+        if resource_type.is_empty()
+            || !resource_type.bytes().all(|b| b.is_ascii_lowercase())
+        {
+            return Err(Error::invalid_id(id, "resource type must be lowercase"));
+        }
+        if suffix.is_empty() {
+            return Err(Error::invalid_id(id, "missing ID after type"));
+        }
+        Ok(IdParts {
+            id,
+            visibility,
+            resource_type,
+            suffix,
+        })
+    }
+
+    /// Check that our suffix looks like something BigML would generate.
+    /// Normal IDs end in 24 hexadecimal digits, and shared IDs end in an
+    /// alphanumeric hash.
+    fn validate(&self) -> Result<()> {
+        let valid = match self.visibility {
+            Some("shared") => self.suffix.bytes().all(|b| b.is_ascii_alphanumeric()),
+            _ => {
+                self.suffix.len() == 24
+                    && self
+                        .suffix
+                        .bytes()
+                        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+            }
+        };
+        if valid {
+            Ok(())
+        } else if self.visibility == Some("shared") {
+            Err(Error::invalid_id(
+                self.id,
+                "expected an alphanumeric hash after type",
+            ))
+        } else {
+            Err(Error::invalid_id(
+                self.id,
+                "expected 24 lowercase hexadecimal digits after type",
+            ))
+        }
+    }
+}
+
+/// A BigML resource ID of any type, such as `"dataset/5e5e…"`. Use this
+/// when you don't know the type of a resource at compile time. Use
+/// `Client::fetch_any` to fetch the corresponding resource.
+///
+/// ```
+/// use bigml::resource::AnyId;
+///
+/// let id: AnyId = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse()?;
+/// assert_eq!(id.resource_type(), "dataset");
+/// # Ok::<(), bigml::Error>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AnyId {
+    /// The ID of the resource.
+    id: String,
+}
+
+impl AnyId {
+    /// Get this resource as a string.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// The type of this resource, as it appears in the ID, such as
+    /// `"dataset"`.
+    pub fn resource_type(&self) -> &str {
+        let rest = self
+            .id
+            .strip_prefix("shared/")
+            .or_else(|| self.id.strip_prefix("public/"))
+            .unwrap_or(&self.id);
+        rest.split('/').next().unwrap_or(rest)
+    }
+}
+
+impl AsRef<str> for AnyId {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Borrow<str> for AnyId {
+    fn borrow(&self) -> &str {
+        &self.id
+    }
+}
+
+impl PartialEq<str> for AnyId {
+    fn eq(&self, other: &str) -> bool {
+        self.id == other
+    }
+}
+
+impl PartialEq<&str> for AnyId {
+    fn eq(&self, other: &&str) -> bool {
+        self.id == *other
+    }
+}
+
+impl FromStr for AnyId {
+    type Err = Error;
+
+    fn from_str(id: &str) -> Result<Self> {
+        IdParts::parse(id)?.validate()?;
+        Ok(AnyId { id: id.to_owned() })
+    }
+}
+
+impl<R: Resource> From<Id<R>> for AnyId {
+    fn from(id: Id<R>) -> AnyId {
+        AnyId { id: id.id }
+    }
+}
+
+/// Convert an `AnyId` back into a typed `Id`, failing if it refers to a
+/// different type of resource.
+///
+/// ```
+/// use bigml::resource::{AnyId, Dataset, Id, Source};
+/// use std::convert::TryFrom;
+///
+/// let any: AnyId = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse()?;
+/// assert!(Id::<Source>::try_from(any.clone()).is_err());
+/// let id = Id::<Dataset>::try_from(any)?;
+/// assert_eq!(id.resource_type(), "dataset");
+/// # Ok::<(), bigml::Error>(())
+/// ```
+impl<R: Resource> TryFrom<AnyId> for Id<R> {
+    type Error = Error;
+
+    fn try_from(id: AnyId) -> Result<Self> {
+        id.as_str().parse()
+    }
+}
+
+impl fmt::Debug for AnyId {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", &self.id)
+    }
+}
+
+impl fmt::Display for AnyId {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", &self.id)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // We apply the same checks as `FromStr`, so that every `AnyId` we
+        // hand out can be parsed again.
+        let id: String = String::deserialize(deserializer)?;
+        match IdParts::parse(&id).and_then(|parts| parts.validate()) {
+            Ok(()) => Ok(AnyId { id }),
+            Err(_) => Err(<D::Error as serde::de::Error>::invalid_value(
+                Unexpected::Str(&id),
+                &"a BigML resource ID",
+            )),
+        }
+    }
+}
+
+impl Serialize for AnyId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.id.serialize(serializer)
+    }
+}
+
 impl<R: Resource> fmt::Debug for Id<R> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{}", &self.id)
@@ -71,20 +426,19 @@ impl<'de, R: Resource> Deserialize<'de> for Id<R> {
     where
         D: Deserializer<'de>,
     {
+        // Use the same rules as `FromStr`, so that IDs survive a round trip
+        // and shared or public IDs are accepted.
         let id: String = String::deserialize(deserializer)?;
-        if id.starts_with(R::id_prefix()) {
-            Ok(Id {
-                id,
-                _phantom: PhantomData,
-            })
-        } else {
-            let unexpected = Unexpected::Str(&id);
-            let expected =
-                format!("a BigML resource ID starting with '{}'", R::id_prefix());
-            Err(<D::Error as serde::de::Error>::invalid_value(
-                unexpected,
-                &&expected[..],
-            ))
+        match id.parse::<Id<R>>() {
+            Ok(parsed) => Ok(parsed),
+            Err(_) => {
+                let unexpected = Unexpected::Str(&id);
+                let expected = format!("a BigML {} ID", R::resource_type());
+                Err(<D::Error as serde::de::Error>::invalid_value(
+                    unexpected,
+                    &&expected[..],
+                ))
+            }
         }
     }
 }
@@ -97,3 +451,128 @@ impl<R: Resource> Serialize for Id<R> {
         self.id.serialize(serializer)
     }
 }
+
+#[test]
+fn parse_any_id() {
+    let id = "source/5e5e5e5e5e5e5e5e5e5e5e5e".parse::<AnyId>().unwrap();
+    assert_eq!(id.resource_type(), "source");
+    assert_eq!(id.as_str(), "source/5e5e5e5e5e5e5e5e5e5e5e5e");
+    for bad in &["", "source", "source/", "/5e5e", "Source/5e5e"] {
+        assert!(bad.parse::<AnyId>().is_err(), "{:?} should not parse", bad);
+    }
+}
+
+#[test]
+fn parse_validates_id_suffix() {
+    use super::Dataset;
+
+    for good in &[
+        "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "public/dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "shared/dataset/abcXYZ123",
+    ] {
+        assert!(
+            good.parse::<Id<Dataset>>().is_ok(),
+            "{:?} should parse",
+            good
+        );
+        let any = good.parse::<AnyId>().unwrap();
+        assert_eq!(any.resource_type(), "dataset");
+    }
+    for bad in &[
+        "dataset/",
+        "dataset/has spaces",
+        "dataset/5e5e",
+        "dataset/5E5E5E5E5E5E5E5E5E5E5E5E",
+        "shared/dataset/not-alphanumeric",
+    ] {
+        let err = bad.parse::<Id<Dataset>>().unwrap_err();
+        assert!(matches!(err, Error::InvalidId { .. }), "{:?}: {}", bad, err);
+    }
+    assert!(matches!(
+        "source/5e5e5e5e5e5e5e5e5e5e5e5e".parse::<Id<Dataset>>(),
+        Err(Error::WrongResourceType { .. }),
+    ));
+}
+
+#[test]
+fn deserialize_validates_ids() {
+    use super::Dataset;
+
+    for good in &[
+        "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "public/dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "shared/dataset/abcXYZ123",
+    ] {
+        let json = serde_json::to_value(good).unwrap();
+        let id: Id<Dataset> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&id).unwrap(), json);
+        let any = AnyId::from(id);
+        assert_eq!(any.resource_type(), "dataset");
+        let any: AnyId = serde_json::from_value(json).unwrap();
+        assert_eq!(any.resource_type(), "dataset");
+    }
+    for bad in &["dataset/1", "source/5e5e5e5e5e5e5e5e5e5e5e5e", "dataset"] {
+        let json = serde_json::to_value(bad).unwrap();
+        assert!(
+            serde_json::from_value::<Id<Dataset>>(json).is_err(),
+            "{}",
+            bad
+        );
+    }
+    assert!(serde_json::from_value::<AnyId>(serde_json::json!("dataset/1")).is_err());
+}
+
+#[test]
+fn convert_between_id_and_any_id() {
+    use super::{Dataset, Source};
+
+    let id = "source/5e5e5e5e5e5e5e5e5e5e5e5e"
+        .parse::<Id<Source>>()
+        .unwrap();
+    assert_eq!(id.resource_type(), "source");
+    let any = AnyId::from(id.clone());
+    assert_eq!(any.resource_type(), "source");
+    assert_eq!(Id::<Source>::try_from(any.clone()).unwrap(), id);
+    assert!(Id::<Dataset>::try_from(any).is_err());
+}
+
+#[test]
+fn ids_work_like_strings() {
+    use super::Source;
+    use std::collections::HashMap;
+
+    let raw = "source/5e5e5e5e5e5e5e5e5e5e5e5e";
+    let id = raw.parse::<Id<Source>>().unwrap();
+    assert!(id == raw);
+    assert!(id == *raw);
+    let any = AnyId::from(id.clone());
+    assert!(any == raw);
+
+    let mut names = HashMap::new();
+    names.insert(id, "example");
+    assert_eq!(names.get(raw), Some(&"example"));
+}
+
+#[test]
+fn resource_types_round_trip() {
+    use super::{Dataset, Source};
+
+    assert_eq!(Dataset::resource_type(), ResourceType::Dataset);
+    assert_eq!(Source::resource_type().api_name(), "source");
+    assert_eq!(
+        ResourceType::known("batchprediction"),
+        Some(ResourceType::BatchPrediction)
+    );
+    assert_eq!(ResourceType::known("newthing"), None);
+    assert_eq!(
+        ResourceType::from_api_name("newthing"),
+        ResourceType::Other("newthing")
+    );
+    assert_eq!(ResourceType::Other("newthing").to_string(), "newthing");
+    assert_eq!(
+        "cluster".parse::<ResourceType>().unwrap(),
+        ResourceType::Cluster
+    );
+    assert!("newthing".parse::<ResourceType>().is_err());
+}
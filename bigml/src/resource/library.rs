@@ -4,18 +4,19 @@ use serde::{Deserialize, Serialize};
 
 use super::id::*;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Category, Resource, ResourceCommon, Updatable};
 
 /// A BigML library for use in a WhizzML script.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "library"]
 #[non_exhaustive]
 pub struct Library {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -26,15 +27,20 @@ pub struct Library {
 
     /// The source code of this library.
     pub source_code: String,
+
+    /// The other libraries imported by this library.
+    #[serde(default)]
+    pub imports: Vec<Id<Library>>,
 }
 
 /// Arguments used to create a new BigML script.
-#[derive(Debug, Serialize)]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Library")]
 #[non_exhaustive]
 pub struct Args {
-    /// The category code which best describes this script.
+    /// The category which best describes this library.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub category: Option<i64>,
+    pub category: Option<Category>,
 
     /// A human-readable description of this script.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -69,7 +75,3 @@ impl Args {
         }
     }
 }
-
-impl super::Args for Args {
-    type Resource = Library;
-}
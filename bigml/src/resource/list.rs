@@ -0,0 +1,142 @@
+//! Types used to list existing resources.
+
+use serde::{Deserialize, Serialize};
+use url::form_urlencoded;
+
+use super::id::*;
+use super::status::*;
+use super::Resource;
+
+/// Which resources should `Client::list` return? This uses a "builder"
+/// pattern:
+///
+/// ```
+/// use bigml::resource::ListFilter;
+///
+/// let filter = ListFilter::default().tag("nightly").page_size(50);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ListFilter {
+    /// Query parameters to pass to BigML.
+    params: Vec<(String, String)>,
+    /// How many resources should we fetch per request?
+    page_size: Option<usize>,
+}
+
+impl ListFilter {
+    /// Only return resources with the specified tag.
+    pub fn tag<S: Into<String>>(self, tag: S) -> Self {
+        self.param("tags__in", tag)
+    }
+
+    /// Only return resources with exactly the specified name.
+    pub fn name<S: Into<String>>(self, name: S) -> Self {
+        self.param("name", name)
+    }
+
+    /// Pass an arbitrary filter parameter to BigML, such as `"rows__gt"` or
+    /// `"order_by"`. See BigML's documentation on filtering and ordering.
+    pub fn param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Fetch `page_size` resources per request. This does not limit the total
+    /// number of resources returned.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Build a query string for the page starting at `offset`.
+    pub(crate) fn query(&self, offset: usize) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query.extend_pairs(&self.params);
+        if let Some(page_size) = self.page_size {
+            query.append_pair("limit", &page_size.to_string());
+        }
+        query.append_pair("offset", &offset.to_string());
+        query.finish()
+    }
+}
+
+/// A filter which `Client::list` can use to list resources of type `R`.
+///
+/// `ListFilter` works with any resource type. Resources with `#[filterable]`
+/// fields also have a typed filter, such as `dataset::DatasetListFilter`,
+/// which can only be used to list that type of resource:
+///
+/// ```
+/// use bigml::resource::dataset::DatasetListFilter;
+///
+/// let filter = DatasetListFilter::new().tag("nightly").rows_gt(1000);
+/// ```
+pub trait AsListFilter<R: Resource> {
+    /// Get the underlying untyped filter.
+    fn as_list_filter(&self) -> &ListFilter;
+}
+
+impl<R: Resource> AsListFilter<R> for ListFilter {
+    fn as_list_filter(&self) -> &ListFilter {
+        self
+    }
+}
+
+/// A summary of a resource returned by `Client::list`. BigML omits many
+/// fields from listings, so use `Client::fetch` to get the full resource.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(bound = "")]
+#[non_exhaustive]
+pub struct ListItem<R: Resource> {
+    /// The ID of this resource.
+    pub resource: Id<R>,
+
+    /// The name of this resource.
+    #[serde(default)]
+    pub name: String,
+
+    /// User-defined tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// The status of this resource.
+    pub status: Option<GenericStatus>,
+}
+
+/// A single page of results returned by BigML.
+#[derive(Debug, Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct ListPage<R: Resource> {
+    /// Information about this page.
+    pub(crate) meta: ListMeta,
+    /// The resources on this page.
+    pub(crate) objects: Vec<ListItem<R>>,
+}
+
+/// Information about a `ListPage`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListMeta {
+    /// The path of the next page, if any.
+    pub(crate) next: Option<String>,
+}
+
+#[test]
+fn list_filter_builds_query() {
+    let filter = ListFilter::default().tag("a b").page_size(10);
+    assert_eq!(filter.query(20), "tags__in=a+b&limit=10&offset=20");
+}
+
+#[test]
+fn typed_list_filter_builds_query() {
+    use super::{dataset::DatasetListFilter, Dataset};
+    let filter = DatasetListFilter::new()
+        .tag("a")
+        .rows_gt(10)
+        .columns(3usize);
+    let filter = AsListFilter::<Dataset>::as_list_filter(&filter);
+    assert_eq!(filter.query(0), "tags__in=a&rows__gt=10&columns=3&offset=0");
+}
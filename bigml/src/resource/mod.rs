@@ -5,21 +5,45 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, hash::BuildHasher};
 
 // We re-export everything from our support submodules.
+pub use self::any::*;
+pub use self::category::*;
 pub use self::id::*;
+pub use self::list::*;
+pub use self::operating_point::*;
 pub use self::status::*;
 
 // We only re-export the main names from our resource submodules.  For any
 // other types, use a fully-qualified name.
+#[cfg(feature = "anomalies")]
+pub use self::anomaly::Anomaly;
+#[cfg(feature = "clustering")]
 pub use self::batchcentroid::BatchCentroid;
+#[cfg(feature = "models")]
 pub use self::batchprediction::BatchPrediction;
+#[cfg(feature = "clustering")]
 pub use self::cluster::Cluster;
 pub use self::dataset::Dataset;
+#[cfg(feature = "models")]
 pub use self::ensemble::{Ensemble, EnsembleField};
+#[cfg(feature = "models")]
 pub use self::evaluation::Evaluation;
+#[cfg(feature = "whizzml")]
 pub use self::execution::Execution;
+#[cfg(feature = "timeseries")]
+pub use self::forecast::Forecast;
+#[cfg(feature = "whizzml")]
 pub use self::library::Library;
+#[cfg(feature = "models")]
+pub use self::model::Model;
+#[cfg(feature = "models")]
+pub use self::prediction::Prediction;
+#[cfg(feature = "whizzml")]
 pub use self::script::Script;
 pub use self::source::Source;
+#[cfg(feature = "timeseries")]
+pub use self::timeseries::TimeSeries;
+#[cfg(feature = "topicmodels")]
+pub use self::topicmodel::TopicModel;
 
 /// A shared interface to all BigML resource types.
 ///
@@ -54,6 +78,31 @@ pub use self::source::Source;
 ///     // Resource-specific fields here.
 /// }
 /// ```
+///
+/// If the ID or status are stored in differently-named fields, you can point
+/// the derive at them using `#[resource(id = "...", status = "...")]`. Nested
+/// fields may be written as `"outer.inner"`.
+///
+/// ```
+/// # #[macro_use] extern crate bigml_derive;
+/// # extern crate bigml;
+/// # use serde::{Deserialize, Serialize};
+/// # use bigml::resource::{GenericStatus, Id, Resource, ResourceCommon, Status};
+/// #[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+/// #[api_name = "otherresource"]
+/// #[resource(id = "id", status = "details.status")]
+/// pub struct OtherResource {
+///     #[serde(flatten)]
+///     pub common: ResourceCommon,
+///     pub id: Id<OtherResource>,
+///     pub details: Details,
+/// }
+///
+/// #[derive(Clone, Debug, Deserialize, Serialize)]
+/// pub struct Details {
+///     pub status: GenericStatus,
+/// }
+/// ```
 pub trait Resource:
     fmt::Debug + DeserializeOwned + Send + Serialize + Sync + 'static
 {
@@ -63,6 +112,21 @@ pub trait Resource:
     /// The URL path used to create a new resource of this type.
     fn create_path() -> &'static str;
 
+    /// The URL path used to list resources of this type.
+    fn list_path() -> &'static str {
+        Self::create_path()
+    }
+
+    /// The URL path used to update the resource `id`.
+    fn update_path(id: &Id<Self>) -> String {
+        format!("/{}", id)
+    }
+
+    /// The type of this resource.
+    fn resource_type() -> ResourceType {
+        ResourceType::from_api_name(Self::id_prefix().trim_end_matches('/'))
+    }
+
     /// Fields shared between all resource types. These are "flattened" into the
     /// top-level of the JSON version of this resource.
     fn common(&self) -> &ResourceCommon;
@@ -94,12 +158,24 @@ pub trait Resource:
 /// };
 /// ```
 ///
+/// Outside this crate, or wherever it's more convenient, you can use the
+/// generated chainable setters instead:
+///
+/// ```
+/// use bigml::resource::source::{FieldUpdate, Optype};
+///
+/// let field_update = FieldUpdate::new().optype(Optype::Categorical);
+/// ```
+///
 /// ### Implementing `Updatable` (internal only)
 ///
 /// For primitive types like `String` or `bool`, you should add them to the
 /// `primitive_updatable_types!` macro, which will define `type Update = self`.
-/// You can also do this manually for simple `enum` types, and other values
-/// which can only be updated as a whole.
+/// You can also do this manually for other values which can only be updated
+/// as a whole.
+///
+/// Simple `enum` types without fields can use `#[derive(Updatable)]`, which
+/// also defines `type Update = Self`.
 ///
 /// For struct types, you should use `#[derive(Updatable)]` and mark updatable
 /// fields with `#[updatable]`. For a struct `Foo`, this will generate a
@@ -121,7 +197,7 @@ macro_rules! primitive_updatable_types {
     };
 }
 
-primitive_updatable_types!(bool, i64, String, u16);
+primitive_updatable_types!(bool, Category, i64, String, u16);
 
 /// `HashMap<String, T>` can be updated using `HashMap<String, T::Update>`.
 impl<T: Updatable, H: BuildHasher> Updatable for HashMap<String, T, H> {
@@ -139,6 +215,13 @@ impl<T: Updatable> Updatable for Vec<T> {
 }
 
 /// Arguments which can be used to create a resource.
+///
+/// ### Implementing `Args` (internal only)
+///
+/// Use `#[derive(Args)]` with `#[args(resource = "MyResource")]` to specify
+/// the resource type. This also generates a `with_*` setter for each field,
+/// unless the field is marked with `#[args(skip)]`. Setters for `Option<T>`
+/// fields take a `T`.
 pub trait Args: fmt::Debug + Serialize {
     /// The resource type these arguments create.
     type Resource: Resource;
@@ -150,8 +233,9 @@ pub trait Args: fmt::Debug + Serialize {
 #[derive(Clone, Debug, Deserialize, Serialize, Updatable)]
 #[non_exhaustive]
 pub struct ResourceCommon {
-    /// Used to classify by industry or category.  0 is "Miscellaneous".
-    pub category: i64,
+    /// Used to classify by industry or category.
+    #[updatable]
+    pub category: Category,
 
     /// An HTTP status code, typically either 201 or 200.
     ///
@@ -191,21 +275,47 @@ pub struct ResourceCommon {
 }
 
 // Support modules defining general types.
+mod any;
+mod category;
 mod id;
+mod list;
+mod operating_point;
+#[macro_use]
 mod status;
 
 // Individual resource types.  These need to go after our `response!` macro
 // definition, above, because macros are processed as source is being read.
+// Everything except sources and datasets sits behind a cargo feature.
+#[cfg(feature = "anomalies")]
+pub mod anomaly;
+#[cfg(feature = "clustering")]
 pub mod batchcentroid;
+#[cfg(feature = "models")]
 pub mod batchprediction;
+#[cfg(feature = "clustering")]
 pub mod cluster;
 pub mod dataset;
+#[cfg(feature = "models")]
 pub mod ensemble;
+#[cfg(feature = "models")]
 pub mod evaluation;
+#[cfg(feature = "whizzml")]
 pub mod execution;
+#[cfg(feature = "timeseries")]
+pub mod forecast;
+#[cfg(feature = "whizzml")]
 pub mod library;
+#[cfg(feature = "models")]
+pub mod model;
+#[cfg(feature = "models")]
+pub mod prediction;
+#[cfg(feature = "whizzml")]
 pub mod script;
 pub mod source;
+#[cfg(feature = "timeseries")]
+pub mod timeseries;
+#[cfg(feature = "topicmodels")]
+pub mod topicmodel;
 
 #[test]
 fn can_compile_update_struct_expressions() {
@@ -0,0 +1,247 @@
+//! A decision tree model.
+//!
+//! https://bigml.com/api/models
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::id::*;
+use super::source::Field;
+use super::status::*;
+use super::{Dataset, Resource, ResourceCommon};
+
+/// A decision tree model. To make predictions without calling the BigML API,
+/// see `bigml::local::LocalModel`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[api_name = "model"]
+#[non_exhaustive]
+pub struct Model {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Model>,
+
+    /// The current status of this model.
+    pub status: GenericStatus,
+
+    /// The IDs of the fields this model predicts. In practice, this contains a
+    /// single field.
+    #[serde(default)]
+    pub objective_fields: Vec<String>,
+
+    /// The structure of this model. Will be missing or incomplete while the
+    /// model is being created.
+    #[serde(default)]
+    pub model: Option<ModelInfo>,
+}
+
+impl Model {
+    /// How important each field is to this model's predictions, most
+    /// important first. Empty while the model is being created.
+    pub fn field_importance(&self) -> Vec<FieldImportance> {
+        match &self.model {
+            Some(info) => field_importance(
+                info.importance.iter().map(|(id, imp)| (id, *imp)),
+                |id| info.fields.get(id).map(|field| field.name.clone()),
+            ),
+            None => vec![],
+        }
+    }
+}
+
+/// The structure of a decision tree model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ModelInfo {
+    /// Metadata describing each field used by this model. Keyed by BigML
+    /// field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+
+    /// The root of our decision tree.
+    pub root: Option<Node>,
+
+    /// How important each field is to this model's predictions, as pairs of
+    /// BigML field IDs and importance. Use `Model::field_importance` to get
+    /// these with field names.
+    #[serde(default)]
+    pub importance: Vec<(String, f64)>,
+}
+
+/// How important a field is to a model's predictions.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FieldImportance {
+    /// The BigML field ID.
+    pub field_id: String,
+
+    /// The name of the field, if we know it.
+    pub name: Option<String>,
+
+    /// The importance of this field. The importances of all fields add up to
+    /// 1.0.
+    pub importance: f64,
+}
+
+/// Build a list of `FieldImportance` values, most important first, looking up
+/// names using `name_of`.
+pub(crate) fn field_importance<'a, I, F>(
+    importance: I,
+    name_of: F,
+) -> Vec<FieldImportance>
+where
+    I: IntoIterator<Item = (&'a String, f64)>,
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = importance
+        .into_iter()
+        .map(|(field_id, importance)| FieldImportance {
+            field_id: field_id.to_owned(),
+            name: name_of(field_id),
+            importance,
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| {
+        b.importance
+            .total_cmp(&a.importance)
+            .then_with(|| a.field_id.cmp(&b.field_id))
+    });
+    result
+}
+
+/// A node in a decision tree.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Node {
+    /// The condition which must be true for an input to reach this node.
+    pub predicate: Predicate,
+
+    /// The value predicted by this node.
+    pub output: Value,
+
+    /// How confident we are in `output`. Only present for classification
+    /// models.
+    #[serde(default)]
+    pub confidence: Option<f64>,
+
+    /// The number of training instances which reached this node.
+    #[serde(default)]
+    pub count: Option<u64>,
+
+    /// The child nodes of this node, if any.
+    #[serde(default)]
+    pub children: Vec<Node>,
+}
+
+/// The condition which must be true for an input to reach a `Node`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum Predicate {
+    /// A constant predicate. The root node uses `true`.
+    Constant(bool),
+    /// Compare the value of a field against a constant.
+    Split(Split),
+}
+
+/// Compare the value of a field against a constant.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Split {
+    /// The BigML field ID to check.
+    pub field: String,
+
+    /// The comparison operator, such as `"<="` or `"="`. A trailing `"*"`
+    /// means that the predicate is also true when the field is missing.
+    /// (Anomaly detectors call this `op`.)
+    #[serde(alias = "op")]
+    pub operator: String,
+
+    /// The value to compare against. For text and items fields, this is the
+    /// number of times `term` appears.
+    pub value: Value,
+
+    /// For text and items fields, the term to count.
+    #[serde(default)]
+    pub term: Option<String>,
+}
+
+/// Arguments used to create a model.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Model")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the BigML `Dataset` to use as training data.
+    pub dataset: Id<Dataset>,
+
+    /// The ID of the field to predict. Defaults to the last field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objective_field: Option<String>,
+
+    /// The name of this model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args`.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            objective_field: None,
+            name: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn model_field_importance_has_names() {
+    use serde_json::json;
+
+    let model: Model = serde_json::from_value(json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "model/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The model has been created" },
+        "objective_fields": ["000004"],
+        "model": {
+            "fields": {
+                "000002": { "name": "petal length", "optype": "numeric" },
+                "000004": { "name": "species", "optype": "categorical" }
+            },
+            "importance": [["000002", 0.9], ["000001", 0.1]]
+        }
+    }))
+    .unwrap();
+    assert_eq!(
+        model.field_importance(),
+        vec![
+            FieldImportance {
+                field_id: "000002".to_owned(),
+                name: Some("petal length".to_owned()),
+                importance: 0.9,
+            },
+            FieldImportance {
+                field_id: "000001".to_owned(),
+                name: None,
+                importance: 0.1,
+            },
+        ],
+    );
+}
@@ -0,0 +1,59 @@
+//! Choosing how classifiers pick a predicted class.
+
+use serde::{Deserialize, Serialize};
+
+/// Which measure BigML uses to choose between classes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum OperatingKind {
+    /// Use the probability of each class.
+    Probability,
+    /// Use the confidence of each class, which is more conservative than
+    /// probability when there is little data.
+    Confidence,
+    /// Use the fraction of ensemble members voting for each class. Only
+    /// available for ensembles.
+    Votes,
+}
+
+/// Predict `positive_class` whenever its `kind` measure reaches `threshold`,
+/// instead of predicting the most likely class. This is mostly useful for
+/// imbalanced classes, where the rare class is seldom the most likely one.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct OperatingPoint {
+    /// The measure compared against `threshold`.
+    pub kind: OperatingKind,
+
+    /// The class to predict when `threshold` is reached.
+    pub positive_class: String,
+
+    /// The threshold, between 0.0 and 1.0.
+    pub threshold: f64,
+}
+
+impl OperatingPoint {
+    /// Create a new `OperatingPoint`.
+    pub fn new<S: Into<String>>(
+        kind: OperatingKind,
+        positive_class: S,
+        threshold: f64,
+    ) -> OperatingPoint {
+        OperatingPoint {
+            kind,
+            positive_class: positive_class.into(),
+            threshold,
+        }
+    }
+}
+
+#[test]
+fn serialize_operating_point() {
+    use serde_json::json;
+    let point = OperatingPoint::new(OperatingKind::Probability, "fraud", 0.2);
+    assert_eq!(
+        json!(point),
+        json!({ "kind": "probability", "positive_class": "fraud", "threshold": 0.2 }),
+    );
+}
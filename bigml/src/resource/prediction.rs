@@ -0,0 +1,163 @@
+//! A single prediction made by BigML.
+//!
+//! https://bigml.com/api/predictions
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::id::*;
+use super::operating_point::*;
+use super::status::*;
+use super::{Model, Resource, ResourceCommon};
+
+/// A single prediction made by BigML. To predict many rows at once, see
+/// `BatchPrediction`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[api_name = "prediction"]
+#[non_exhaustive]
+pub struct Prediction {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Prediction>,
+
+    /// The current status of this prediction.
+    pub status: GenericStatus,
+
+    /// The supervised model used to make this prediction.
+    #[serde(default)]
+    pub model: Option<AnyId>,
+
+    /// The predicted value.
+    #[serde(default)]
+    pub output: Value,
+
+    /// How confident BigML is in `output`. Only present for classification
+    /// models.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+
+    /// The probability of `output`. Only present for classification
+    /// models.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability: Option<f64>,
+
+    /// How much each input field contributed to this prediction. Only
+    /// present if `Args::explain` was set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explanation: Vec<FieldContribution>,
+}
+
+/// How much a single field contributed to a `Prediction`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "(String, f64)", into = "(String, f64)")]
+#[non_exhaustive]
+pub struct FieldContribution {
+    /// The BigML field ID.
+    pub field_id: String,
+
+    /// How much this field contributed.
+    pub contribution: f64,
+}
+
+// BigML represents contributions as `[field_id, contribution]` pairs.
+impl From<(String, f64)> for FieldContribution {
+    fn from((field_id, contribution): (String, f64)) -> Self {
+        FieldContribution {
+            field_id,
+            contribution,
+        }
+    }
+}
+
+impl From<FieldContribution> for (String, f64) {
+    fn from(contribution: FieldContribution) -> Self {
+        (contribution.field_id, contribution.contribution)
+    }
+}
+
+/// Arguments used to create a prediction.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Prediction")]
+#[non_exhaustive]
+pub struct Args {
+    /// The model used to make this prediction.
+    pub model: Id<Model>,
+
+    /// The input values, keyed by field name or BigML field ID.
+    pub input_data: HashMap<String, Value>,
+
+    /// Should BigML explain which fields contributed to this prediction?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<bool>,
+
+    /// Predict a specific class once its probability, confidence or votes
+    /// reach a threshold. Only used for classification models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operating_point: Option<OperatingPoint>,
+
+    /// Choose classes using this measure, instead of the default for the
+    /// model. Ignored if `operating_point` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operating_kind: Option<OperatingKind>,
+
+    /// The name of this prediction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` which uses `model` to make a prediction for
+    /// `input_data`.
+    pub fn new(model: Id<Model>, input_data: HashMap<String, Value>) -> Args {
+        Args {
+            model,
+            input_data,
+            explain: None,
+            operating_point: None,
+            operating_kind: None,
+            name: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_explained_prediction() {
+    use serde_json::json;
+
+    let prediction: Prediction = serde_json::from_value(json!({
+        "category": 0,
+        "code": 201,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "prediction/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "status": { "code": 5, "message": "The prediction has been created" },
+        "model": "model/5e5e5e5e5e5e5e5e5e5e5e5e",
+        "output": "Iris-setosa",
+        "confidence": 0.9,
+        "explanation": [["000002", 0.6], ["000003", -0.1]]
+    }))
+    .unwrap();
+    assert_eq!(prediction.output, json!("Iris-setosa"));
+    assert_eq!(
+        prediction.explanation,
+        vec![
+            FieldContribution::from(("000002".to_owned(), 0.6)),
+            FieldContribution::from(("000003".to_owned(), -0.1)),
+        ],
+    );
+}
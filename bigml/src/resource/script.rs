@@ -1,24 +1,25 @@
 //! A WhizzML script on BigML.
 
 use serde::{Deserialize, Serialize};
-use std::{fmt, str::FromStr};
+use std::{fmt, fs, path::Path, str::FromStr};
 
 use super::id::*;
 use super::library::Library;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Category, Resource, ResourceCommon, Updatable};
 use crate::errors::*;
 
 /// A WhizzML script on BigML.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "script"]
 #[non_exhaustive]
 pub struct Script {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -29,15 +30,48 @@ pub struct Script {
 
     /// The source code of this script.
     pub source_code: String,
+
+    /// The libraries imported by this script.
+    #[serde(default)]
+    pub imports: Vec<Id<Library>>,
+
+    /// The inputs declared by this script.
+    #[serde(default)]
+    pub inputs: Vec<Input>,
+
+    /// The outputs declared by this script.
+    #[serde(default)]
+    pub outputs: Vec<Output>,
+}
+
+impl Script {
+    /// Start building the `Args` for a new script.
+    ///
+    /// ```
+    /// use bigml::resource::{script::Type, Script};
+    ///
+    /// let args = Script::builder("(define out (* n 2))")
+    ///     .name("double")
+    ///     .input("n", Type::Integer)
+    ///     .output("out", Type::Integer)
+    ///     .tag("example")
+    ///     .build();
+    /// ```
+    pub fn builder<S: Into<String>>(source_code: S) -> ArgsBuilder {
+        ArgsBuilder {
+            args: Args::new(source_code),
+        }
+    }
 }
 
 /// Arguments used to create a new BigML script.
-#[derive(Debug, Serialize)]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Script")]
 #[non_exhaustive]
 pub struct Args {
-    /// The category code which best describes this script.
+    /// The category which best describes this script.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub category: Option<i64>,
+    pub category: Option<Category>,
 
     /// A human-readable description of this script.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -83,8 +117,109 @@ impl Args {
     }
 }
 
-impl super::Args for Args {
-    type Resource = Script;
+/// A builder for script `Args`, created using `Script::builder` or
+/// `ArgsBuilder::with_source_file`.
+#[derive(Debug)]
+pub struct ArgsBuilder {
+    args: Args,
+}
+
+impl ArgsBuilder {
+    /// Start building a script using the source code in `path`.
+    pub fn with_source_file<P: AsRef<Path>>(path: P) -> Result<ArgsBuilder> {
+        let path = path.as_ref();
+        let source_code = fs::read_to_string(path)
+            .map_err(|err| Error::could_not_read_file(path, err))?;
+        Ok(Script::builder(source_code))
+    }
+
+    /// Set the category which best describes this script.
+    pub fn category(mut self, category: Category) -> Self {
+        self.args.category = Some(category);
+        self
+    }
+
+    /// Set a human-readable description of this script.
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.args.description = Some(description.into());
+        self
+    }
+
+    /// Import a library.
+    pub fn import(mut self, library: Id<Library>) -> Self {
+        self.args.imports.push(library);
+        self
+    }
+
+    /// Declare an input with the specified name and type.
+    pub fn input<S: Into<String>>(self, name: S, type_: Type) -> Self {
+        self.input_decl(Input::new(name, type_))
+    }
+
+    /// Declare an input, including any default value or description.
+    pub fn input_decl(mut self, input: Input) -> Self {
+        self.args.inputs.push(input);
+        self
+    }
+
+    /// Set a human-readable name for this script.
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.args.name = Some(name.into());
+        self
+    }
+
+    /// Declare an output with the specified name and type.
+    pub fn output<S: Into<String>>(self, name: S, type_: Type) -> Self {
+        self.output_decl(Output::new(name, type_))
+    }
+
+    /// Declare an output, including any description.
+    pub fn output_decl(mut self, output: Output) -> Self {
+        self.args.outputs.push(output);
+        self
+    }
+
+    /// Add a user-defined tag.
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.args.tags.push(tag.into());
+        self
+    }
+
+    /// Return the finished `Args`.
+    pub fn build(self) -> Args {
+        self.args
+    }
+}
+
+/// The result of `Client::validate_script`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Validation {
+    /// The script compiled successfully.
+    Valid,
+    /// The script could not be compiled.
+    Invalid(CompileError),
+}
+
+impl Validation {
+    /// Did the script compile successfully?
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Validation::Valid)
+    }
+}
+
+/// An error reported by BigML when compiling a WhizzML script.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CompileError {
+    /// The error message returned by BigML.
+    pub message: String,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message.fmt(f)
+    }
 }
 
 /// A script input declaration.
@@ -225,6 +360,27 @@ declare_type_enum! {
     Configuration => "configuration-id",
 }
 
+#[test]
+fn build_script_args() {
+    use serde_json::json;
+    let args = Script::builder("(define n_times_2 (* n 2))")
+        .name("example")
+        .input("n", Type::Integer)
+        .output("n_times_2", Type::Integer)
+        .tag("test")
+        .build();
+    assert_eq!(
+        json!(args),
+        json!({
+            "inputs": [{"name": "n", "type": "integer", "default": null, "description": null}],
+            "name": "example",
+            "outputs": [{"name": "n_times_2", "type": "integer", "description": null}],
+            "source_code": "(define n_times_2 (* n 2))",
+            "tags": ["test"],
+        }),
+    );
+}
+
 #[test]
 fn parse_type() {
     let ty: Type = "categorical".parse().unwrap();
@@ -2,6 +2,7 @@
 
 use serde::de::Unexpected;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 
 /// A BigML status code.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -104,6 +105,17 @@ pub trait Status {
     /// Number between 0.0 and 1.0 representing the progress of creating
     /// this resource.
     fn progress(&self) -> Option<f32>;
+
+    /// BigML's numeric error code, if something went wrong. These are more
+    /// stable than `message`, so prefer matching on them.
+    fn error_code(&self) -> Option<i64> {
+        None
+    }
+
+    /// The underlying cause of an error, if BigML told us.
+    fn cause(&self) -> Option<&Cause> {
+        None
+    }
 }
 
 /// Status of a generic resource.
@@ -122,6 +134,14 @@ pub struct GenericStatus {
     /// Number between 0.0 and 1.0 representing the progress of creating
     /// this resource.
     pub progress: Option<f32>,
+
+    /// BigML's numeric error code, if something went wrong.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<i64>,
+
+    /// The underlying cause of an error, if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Cause>,
 }
 
 impl Status for GenericStatus {
@@ -140,4 +160,83 @@ impl Status for GenericStatus {
     fn progress(&self) -> Option<f32> {
         self.progress
     }
+
+    fn error_code(&self) -> Option<i64> {
+        self.error
+    }
+
+    fn cause(&self) -> Option<&Cause> {
+        self.cause.as_ref()
+    }
+}
+
+/// Implement `Status` for a detailed status type, by delegating to a
+/// `GenericStatus` stored in its `common` field.
+macro_rules! status_with_common_fields {
+    ($ty:ty) => {
+        impl $crate::resource::Status for $ty {
+            fn code(&self) -> $crate::resource::StatusCode {
+                self.common.code()
+            }
+
+            fn message(&self) -> &str {
+                self.common.message()
+            }
+
+            fn elapsed(&self) -> Option<u64> {
+                self.common.elapsed()
+            }
+
+            fn progress(&self) -> Option<f32> {
+                self.common.progress()
+            }
+
+            fn error_code(&self) -> Option<i64> {
+                self.common.error_code()
+            }
+
+            fn cause(&self) -> Option<&$crate::resource::Cause> {
+                self.common.cause()
+            }
+        }
+    };
+}
+
+/// The cause of an error.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Cause {
+    /// The error code of the underlying error.
+    pub code: i64,
+
+    /// Extra information about the underlying error (may be a list or
+    /// hash, possibly other things).
+    #[serde(default)]
+    pub extra: serde_json::Value,
+
+    /// The HTTP status related to the underlying error.
+    pub http_status: Option<u16>,
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "code: {}", self.code)?;
+        if let Some(http_status) = self.http_status {
+            write!(f, ", HTTP status: {}", http_status)?;
+        }
+        write!(f, ", extra: {}", self.extra,)
+    }
+}
+
+#[test]
+fn deserialize_generic_error_status() {
+    let json = r#"{"code": -1, "message": "Unable to parse the source", "error": -1100, "cause": {"code": -1102, "extra": ["bad line"]}, "elapsed": 120}"#;
+    let status: GenericStatus = serde_json::from_str(json).unwrap();
+    assert_eq!(status.error_code(), Some(-1100));
+    assert_eq!(status.cause().unwrap().code, -1102);
+
+    let json = r#"{"code": 5, "message": "The source has been created"}"#;
+    let status: GenericStatus = serde_json::from_str(json).unwrap();
+    assert_eq!(status.error_code(), None);
+    assert!(status.cause().is_none());
 }
@@ -0,0 +1,66 @@
+//! A time series model, used to make forecasts.
+//!
+//! https://bigml.com/api/timeseries
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::status::*;
+use super::{Dataset, Resource, ResourceCommon};
+
+/// A time series model. Use `forecast::Args` to forecast future values.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[api_name = "timeseries"]
+#[non_exhaustive]
+pub struct TimeSeries {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<TimeSeries>,
+
+    /// The current status of this time series.
+    pub status: GenericStatus,
+
+    /// The IDs of the fields this time series models.
+    #[serde(default)]
+    pub objective_fields: Vec<String>,
+}
+
+/// Arguments used to create a time series.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "TimeSeries")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the BigML `Dataset` to use as training data.
+    pub dataset: Id<Dataset>,
+
+    /// The IDs of the numeric fields to model. Defaults to the last numeric
+    /// field.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub objective_fields: Vec<String>,
+
+    /// The name of this time series.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args`.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            objective_fields: vec![],
+            name: None,
+            tags: vec![],
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! A topic model, which finds the topics discussed in text fields.
+//!
+//! https://bigml.com/api/topicmodels
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::status::*;
+use super::{Dataset, Resource, ResourceCommon};
+
+/// A topic model.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[api_name = "topicmodel"]
+#[non_exhaustive]
+pub struct TopicModel {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<TopicModel>,
+
+    /// The current status of this topic model.
+    pub status: GenericStatus,
+
+    /// The topics found. Will be missing while the topic model is being
+    /// created.
+    #[serde(default)]
+    pub topic_model: Option<TopicModelInfo>,
+}
+
+/// The structure of a topic model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct TopicModelInfo {
+    /// The topics found in the training data.
+    #[serde(default)]
+    pub topics: Vec<Topic>,
+}
+
+/// A single topic.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Topic {
+    /// BigML's ID for this topic.
+    pub id: String,
+
+    /// A name for this topic, based on its most common terms.
+    pub name: String,
+}
+
+/// Arguments used to create a topic model.
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "TopicModel")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the BigML `Dataset` to use as training data.
+    pub dataset: Id<Dataset>,
+
+    /// The number of topics to find.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_topics: Option<u32>,
+
+    /// The language of the text, such as `"en"`, used for stemming and stop
+    /// words. Defaults to the language BigML detects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Should pairs of adjacent words be treated as terms?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bigrams: Option<bool>,
+
+    /// Should terms which differ only by case be treated as different?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+
+    /// The maximum number of terms to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_limit: Option<u32>,
+
+    /// Terms to ignore.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_terms: Vec<String>,
+
+    /// The name of this topic model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args`.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            number_of_topics: None,
+            language: None,
+            bigrams: None,
+            case_sensitive: None,
+            term_limit: None,
+            excluded_terms: vec![],
+            name: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn args_with_text_options() {
+    use serde_json::json;
+    let dataset: Id<Dataset> = "dataset/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let args = Args::from_dataset(dataset)
+        .with_language("en")
+        .with_bigrams(true)
+        .with_excluded_terms(vec!["lorem".to_owned()]);
+    assert_eq!(
+        json!(args),
+        json!({
+            "dataset": "dataset/5e5e5e5e5e5e5e5e5e5e5e5e",
+            "language": "en",
+            "bigrams": true,
+            "excluded_terms": ["lorem"],
+        }),
+    );
+}
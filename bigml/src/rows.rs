@@ -0,0 +1,242 @@
+//! Parsing downloaded CSV data into rows, without buffering the whole file.
+
+use bytes::{Buf, BytesMut};
+use csv::{ByteRecord, StringRecord};
+use csv_core::ReadRecordResult;
+use futures::{future, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::{
+    fmt, io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio_util::{
+    codec::{Decoder, FramedRead},
+    io::StreamReader,
+};
+
+use crate::errors::*;
+use crate::transport::{BoxStream, MaybeSend};
+
+/// Parse the body of `res` as a stream of CSV records, including the header.
+pub(crate) fn csv_records(
+    res: reqwest::Response,
+) -> impl Stream<Item = Result<StringRecord>> + MaybeSend {
+    let body = res.bytes_stream().map_err(io::Error::other);
+    FramedRead::new(StreamReader::new(body), CsvRecordDecoder::default())
+}
+
+/// The rows of a downloaded dataset, parsed lazily as the CSV arrives.
+///
+/// This is a `Stream` of `StringRecord` values, not including the header,
+/// which is available from `headers`. Only a small part of the download is
+/// held in memory at once, so this works well for very large datasets.
+/// Normally created using `Client::download_dataset_rows` or
+/// `Client::download_csv`.
+pub struct DatasetRows {
+    /// The header row.
+    headers: StringRecord,
+    /// The remaining rows.
+    records: BoxStream<'static, Result<StringRecord>>,
+}
+
+impl DatasetRows {
+    /// Read the header from `records`, and stream the remaining rows.
+    pub(crate) async fn new<S>(records: S) -> Result<DatasetRows>
+    where
+        S: Stream<Item = Result<StringRecord>> + MaybeSend + 'static,
+    {
+        let mut records: BoxStream<_> = Box::pin(records);
+        let headers = records.try_next().await?.unwrap_or_default();
+        Ok(DatasetRows { headers, records })
+    }
+
+    /// The column names from the header row.
+    pub fn headers(&self) -> &StringRecord {
+        &self.headers
+    }
+
+    /// Deserialize each row into a `T`, matching columns to fields using the
+    /// header.
+    pub fn deserialize<T>(self) -> impl Stream<Item = Result<T>> + MaybeSend
+    where
+        T: DeserializeOwned + MaybeSend,
+    {
+        let DatasetRows { headers, records } = self;
+        let mut row = 0;
+        records.and_then(move |record| {
+            row += 1;
+            future::ready(
+                record
+                    .deserialize(Some(&headers))
+                    .map_err(|err| Error::could_not_parse_row(row, err)),
+            )
+        })
+    }
+}
+
+impl fmt::Debug for DatasetRows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatasetRows")
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for DatasetRows {
+    type Item = Result<StringRecord>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.records.as_mut().poll_next(cx)
+    }
+}
+
+/// A `Decoder` which splits a byte stream into CSV records, keeping only one
+/// partial record in memory at a time.
+struct CsvRecordDecoder {
+    /// Our low-level CSV parser.
+    reader: csv_core::Reader,
+    /// The unescaped contents of the fields in the current record.
+    out: Vec<u8>,
+    /// How many bytes of `out` have been filled in.
+    out_len: usize,
+    /// The end offsets of each field in `out`.
+    ends: Vec<usize>,
+    /// How many entries of `ends` have been filled in.
+    ends_len: usize,
+}
+
+impl Default for CsvRecordDecoder {
+    fn default() -> Self {
+        CsvRecordDecoder {
+            reader: csv_core::Reader::new(),
+            out: vec![0; 1024],
+            out_len: 0,
+            ends: vec![0; 16],
+            ends_len: 0,
+        }
+    }
+}
+
+impl CsvRecordDecoder {
+    /// Parse as much of `src` as we can. If `eof` is true, there's no more
+    /// input after `src`.
+    fn read(&mut self, src: &mut BytesMut, eof: bool) -> Result<Option<StringRecord>> {
+        loop {
+            if src.is_empty() && !eof {
+                return Ok(None);
+            }
+            // An empty input tells `csv_core` that we've reached the end.
+            let (result, nin, nout, nend) = self.reader.read_record(
+                &src[..],
+                &mut self.out[self.out_len..],
+                &mut self.ends[self.ends_len..],
+            );
+            src.advance(nin);
+            self.out_len += nout;
+            self.ends_len += nend;
+            match result {
+                ReadRecordResult::InputEmpty => {}
+                ReadRecordResult::OutputFull => {
+                    let len = self.out.len();
+                    self.out.resize(len * 2, 0);
+                }
+                ReadRecordResult::OutputEndsFull => {
+                    let len = self.ends.len();
+                    self.ends.resize(len * 2, 0);
+                }
+                ReadRecordResult::Record => return self.take_record().map(Some),
+                ReadRecordResult::End => return Ok(None),
+            }
+        }
+    }
+
+    /// Convert the record we just parsed into a `StringRecord`, and reset our
+    /// buffers.
+    fn take_record(&mut self) -> Result<StringRecord> {
+        let mut record = ByteRecord::with_capacity(self.out_len, self.ends_len);
+        let mut start = 0;
+        for &end in &self.ends[..self.ends_len] {
+            record.push_field(&self.out[start..end]);
+            start = end;
+        }
+        self.out_len = 0;
+        self.ends_len = 0;
+        StringRecord::from_byte_record(record)
+            .map_err(|err| Error::Other { source: err.into() })
+    }
+}
+
+impl Decoder for CsvRecordDecoder {
+    type Item = StringRecord;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<StringRecord>> {
+        self.read(src, false)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<StringRecord>> {
+        self.read(src, true)
+    }
+}
+
+#[test]
+fn csv_records_are_decoded_in_chunks() {
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Row {
+        b: String,
+        a: u32,
+    }
+
+    let data: &[u8] = b"a,b\n1,\"x\ny\"\n2,\"long, long value\"\n3,z";
+    let mut decoder = CsvRecordDecoder {
+        // Start with tiny buffers, so we need to grow them.
+        out: vec![0; 1],
+        ends: vec![0; 1],
+        ..CsvRecordDecoder::default()
+    };
+    let mut records = vec![];
+    let mut buf = BytesMut::new();
+    // Feed the input a few bytes at a time.
+    for chunk in data.chunks(3) {
+        buf.extend_from_slice(chunk);
+        while let Some(record) = decoder.decode(&mut buf).unwrap() {
+            records.push(record);
+        }
+    }
+    while let Some(record) = decoder.decode_eof(&mut buf).unwrap() {
+        records.push(record);
+    }
+    assert_eq!(records.len(), 4);
+    assert_eq!(&records[1][1], "x\ny");
+
+    let rows = futures::executor::block_on(async {
+        let records = futures::stream::iter(records.into_iter().map(Ok));
+        let rows = DatasetRows::new(records).await?;
+        assert_eq!(rows.headers(), vec!["a", "b"]);
+        rows.deserialize::<Row>().try_collect::<Vec<_>>().await
+    })
+    .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                a: 1,
+                b: "x\ny".to_owned()
+            },
+            Row {
+                a: 2,
+                b: "long, long value".to_owned()
+            },
+            Row {
+                a: 3,
+                b: "z".to_owned()
+            },
+        ],
+    );
+}
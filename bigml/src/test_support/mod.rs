@@ -0,0 +1,211 @@
+//! Helpers for testing code which uses this crate, without talking to BigML.
+//!
+//! This module is only available with the `test_support` feature. It contains
+//! canned JSON for a finished resource of each type we support, in the form
+//! returned by the BigML API, plus helpers which check that a value survives
+//! a round trip through `serde`.
+//!
+//! ```
+//! use bigml::resource::Dataset;
+//! use bigml::test_support::{assert_round_trip, fixture, resource_fixture};
+//!
+//! let dataset: Dataset = resource_fixture();
+//! assert_eq!(dataset.rows, 150);
+//!
+//! // Catch mismatches between our `Serialize` and `Deserialize` impls.
+//! assert_round_trip::<Dataset>(&fixture::<Dataset>());
+//! ```
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::fmt::Debug;
+
+use crate::resource::{Resource, ResourceType};
+
+/// Look up the raw JSON fixture for `resource_type`, if we have one.
+fn fixture_source(resource_type: ResourceType) -> Option<&'static str> {
+    match resource_type {
+        #[cfg(feature = "anomalies")]
+        ResourceType::Anomaly => Some(include_str!("fixtures/anomaly.json")),
+        #[cfg(feature = "clustering")]
+        ResourceType::BatchCentroid => {
+            Some(include_str!("fixtures/batchcentroid.json"))
+        }
+        #[cfg(feature = "models")]
+        ResourceType::BatchPrediction => {
+            Some(include_str!("fixtures/batchprediction.json"))
+        }
+        #[cfg(feature = "clustering")]
+        ResourceType::Cluster => Some(include_str!("fixtures/cluster.json")),
+        ResourceType::Dataset => Some(include_str!("fixtures/dataset.json")),
+        #[cfg(feature = "models")]
+        ResourceType::Ensemble => Some(include_str!("fixtures/ensemble.json")),
+        #[cfg(feature = "models")]
+        ResourceType::Evaluation => Some(include_str!("fixtures/evaluation.json")),
+        #[cfg(feature = "whizzml")]
+        ResourceType::Execution => Some(include_str!("fixtures/execution.json")),
+        #[cfg(feature = "timeseries")]
+        ResourceType::Forecast => Some(include_str!("fixtures/forecast.json")),
+        #[cfg(feature = "whizzml")]
+        ResourceType::Library => Some(include_str!("fixtures/library.json")),
+        #[cfg(feature = "models")]
+        ResourceType::Model => Some(include_str!("fixtures/model.json")),
+        #[cfg(feature = "models")]
+        ResourceType::Prediction => Some(include_str!("fixtures/prediction.json")),
+        #[cfg(feature = "whizzml")]
+        ResourceType::Script => Some(include_str!("fixtures/script.json")),
+        ResourceType::Source => Some(include_str!("fixtures/source.json")),
+        #[cfg(feature = "timeseries")]
+        ResourceType::TimeSeries => Some(include_str!("fixtures/timeseries.json")),
+        #[cfg(feature = "topicmodels")]
+        ResourceType::TopicModel => Some(include_str!("fixtures/topicmodel.json")),
+        _ => None,
+    }
+}
+
+/// Canned JSON for a finished resource of type `resource_type`, or `None` if
+/// this crate doesn't support that type. The resource ID is always
+/// `"<type>/5e5e5e5e5e5e5e5e5e5e5e5e"`.
+pub fn fixture_for(resource_type: ResourceType) -> Option<Value> {
+    fixture_source(resource_type).map(|json| {
+        serde_json::from_str(json).expect("test fixtures should be valid JSON")
+    })
+}
+
+/// Canned JSON for a finished resource of type `R`.
+///
+/// Panics if we don't have a fixture for `R`.
+pub fn fixture<R: Resource>() -> Value {
+    let resource_type = R::resource_type();
+    fixture_for(resource_type)
+        .unwrap_or_else(|| panic!("no test fixture for {}", resource_type))
+}
+
+/// A finished resource of type `R`, deserialized from `fixture::<R>()`.
+///
+/// Panics if we don't have a fixture for `R`, or if it can't be
+/// deserialized.
+pub fn resource_fixture<R: Resource>() -> R {
+    serde_json::from_value(fixture::<R>()).unwrap_or_else(|err| {
+        panic!(
+            "could not deserialize {} fixture: {}",
+            R::resource_type(),
+            err
+        )
+    })
+}
+
+/// Deserialize `json` as a `T`, and check that serializing and deserializing
+/// it again gives the same JSON. Returns the deserialized value.
+///
+/// Fields which `T` doesn't know about are dropped, so we compare our own
+/// output against itself rather than against `json`.
+///
+/// Panics with a description of the problem if the round trip fails.
+pub fn assert_round_trip<T>(json: &Value) -> T
+where
+    T: Debug + DeserializeOwned + Serialize,
+{
+    let type_name = std::any::type_name::<T>();
+    let value: T = serde_json::from_value(json.to_owned()).unwrap_or_else(|err| {
+        panic!("could not deserialize {}: {}\n{:#}", type_name, err, json)
+    });
+    let serialized = serde_json::to_value(&value).unwrap_or_else(|err| {
+        panic!("could not serialize {}: {}\n{:?}", type_name, err, value)
+    });
+    let reparsed: T =
+        serde_json::from_value(serialized.clone()).unwrap_or_else(|err| {
+            panic!(
+                "could not deserialize serialized {}: {}\n{:#}",
+                type_name, err, serialized,
+            )
+        });
+    let reserialized = serde_json::to_value(&reparsed).unwrap_or_else(|err| {
+        panic!("could not serialize {}: {}\n{:?}", type_name, err, reparsed)
+    });
+    assert_eq!(
+        serialized, reserialized,
+        "{} changed after a serialization round trip",
+        type_name,
+    );
+    value
+}
+
+/// Check that our fixture for `R` deserializes, has the expected ID, and
+/// survives a round trip. Returns the deserialized resource.
+pub fn assert_fixture_round_trips<R: Resource>() -> R {
+    let json = fixture::<R>();
+    let resource = assert_round_trip::<R>(&json);
+    assert_eq!(
+        Some(resource.id().as_str()),
+        json.get("resource").and_then(|id| id.as_str()),
+        "{} fixture has the wrong ID",
+        R::resource_type(),
+    );
+    resource
+}
+
+#[test]
+fn every_fixture_round_trips() {
+    use crate::resource::*;
+
+    assert_fixture_round_trips::<Dataset>();
+    assert_fixture_round_trips::<Source>();
+    #[cfg(feature = "anomalies")]
+    assert_fixture_round_trips::<Anomaly>();
+    #[cfg(feature = "clustering")]
+    {
+        assert_fixture_round_trips::<BatchCentroid>();
+        assert_fixture_round_trips::<Cluster>();
+    }
+    #[cfg(feature = "models")]
+    {
+        use evaluation::ClassificationResult;
+
+        assert_fixture_round_trips::<BatchPrediction>();
+        assert_fixture_round_trips::<Ensemble>();
+        assert_fixture_round_trips::<Evaluation<ClassificationResult>>();
+        assert_fixture_round_trips::<Evaluation<Value>>();
+        assert_fixture_round_trips::<Model>();
+        assert_fixture_round_trips::<Prediction>();
+    }
+    #[cfg(feature = "timeseries")]
+    {
+        assert_fixture_round_trips::<Forecast>();
+        assert_fixture_round_trips::<TimeSeries>();
+    }
+    #[cfg(feature = "topicmodels")]
+    assert_fixture_round_trips::<TopicModel>();
+    #[cfg(feature = "whizzml")]
+    {
+        assert_fixture_round_trips::<Execution>();
+        assert_fixture_round_trips::<Library>();
+        assert_fixture_round_trips::<Script>();
+    }
+
+    // Everything we have a fixture for should also work as an `AnyResource`.
+    for api_name in [
+        "anomaly",
+        "batchcentroid",
+        "batchprediction",
+        "cluster",
+        "dataset",
+        "ensemble",
+        "evaluation",
+        "execution",
+        "forecast",
+        "library",
+        "model",
+        "prediction",
+        "script",
+        "source",
+        "timeseries",
+        "topicmodel",
+    ] {
+        if let Some(json) = fixture_for(ResourceType::from_api_name(api_name)) {
+            let any = assert_round_trip::<AnyResource>(&json);
+            assert_eq!(any.id().resource_type(), api_name);
+        }
+    }
+    assert!(fixture_for(ResourceType::Deepnet).is_none());
+}
@@ -0,0 +1,34 @@
+//! Limiting how often we talk to BigML.
+
+use std::{cmp::max, time::Duration};
+use tokio::sync::Mutex;
+
+use crate::time::{sleep, Instant};
+
+/// Spaces out requests so that we make no more than a fixed number per
+/// second. This is shared between all the tasks using a `Client`.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// The minimum time between requests.
+    interval: Duration,
+    /// The earliest time at which we may make our next request.
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Allow up to `requests` requests per second.
+    pub(crate) fn per_second(requests: f64) -> RateLimiter {
+        RateLimiter {
+            interval: Duration::from_secs_f64(1.0 / requests),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until we're allowed to make another request.
+    pub(crate) async fn until_ready(&self) {
+        // We hold the lock while sleeping, so that waiting tasks go in order.
+        let mut next = self.next.lock().await;
+        sleep(next.saturating_duration_since(Instant::now())).await;
+        *next = max(*next, Instant::now()) + self.interval;
+    }
+}
@@ -0,0 +1,41 @@
+//! Clocks and timers which also work under WebAssembly.
+//!
+//! `std::time::Instant::now` and `SystemTime::now` panic on
+//! `wasm32-unknown-unknown`, and tokio's timers need a tokio runtime, so we
+//! use the browser's clock and timers there instead.
+
+use std::time::{Duration, SystemTime};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// The current time.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn system_now() -> SystemTime {
+    SystemTime::now()
+}
+
+/// The current time.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn system_now() -> SystemTime {
+    // `std::time::SystemTime` values work fine, as long as we don't ask `std`
+    // what time it is.
+    let since_epoch = web_time::SystemTime::now()
+        .duration_since(web_time::SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    SystemTime::UNIX_EPOCH + since_epoch
+}
+
+/// Sleep for `duration`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+/// Sleep for `duration`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
@@ -0,0 +1,152 @@
+//! Pluggable HTTP backends.
+
+use futures::FutureExt;
+use std::error::Error as StdError;
+use std::fmt;
+
+/// The error type returned by an `HttpTransport`. Errors which wrap
+/// `reqwest::Error` or `std::io::Error` will be retried by `Client::wait`
+/// and friends when they look temporary.
+pub type TransportError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// Implemented by every `Send` type, except under WebAssembly, where it's
+/// implemented by every type.
+///
+/// `reqwest`'s futures and streams aren't `Send` under WebAssembly, and
+/// neither is anything which waits on them. We use this in place of `Send` for
+/// the futures and streams we return, so that they're `Send` wherever that's
+/// possible.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Implemented by every `Send` type, except under WebAssembly, where it's
+/// implemented by every type.
+///
+/// `reqwest`'s futures and streams aren't `Send` under WebAssembly, and
+/// neither is anything which waits on them. We use this in place of `Send` for
+/// the futures and streams we return, so that they're `Send` wherever that's
+/// possible.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// A boxed future which is `Send`, except under WebAssembly. See `MaybeSend`.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxFuture<'a, T> = futures::future::BoxFuture<'a, T>;
+
+/// A boxed future which is `Send`, except under WebAssembly. See `MaybeSend`.
+#[cfg(target_arch = "wasm32")]
+pub type BoxFuture<'a, T> = futures::future::LocalBoxFuture<'a, T>;
+
+/// A boxed stream which is `Send`, except under WebAssembly. See `MaybeSend`.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxStream<'a, T> = futures::stream::BoxStream<'a, T>;
+
+/// A boxed stream which is `Send`, except under WebAssembly. See `MaybeSend`.
+#[cfg(target_arch = "wasm32")]
+pub type BoxStream<'a, T> = futures::stream::LocalBoxStream<'a, T>;
+
+/// The future returned by `HttpTransport::send`. This is `Send`, except under
+/// WebAssembly, where `reqwest`'s own futures aren't.
+pub type TransportFuture<'a> =
+    BoxFuture<'a, Result<reqwest::Response, TransportError>>;
+
+/// Something which can send HTTP requests on behalf of a `Client`.
+///
+/// By default, a `Client` uses `ReqwestTransport`. You can supply your own
+/// transport using `ClientBuilder::transport`, either to route requests over
+/// another HTTP stack, or to return canned responses in tests. A
+/// `reqwest::Response` can be built from any `http::Response` using `From`.
+///
+/// Rate limiting, concurrency limits and response caching are all handled by
+/// the `Client` before calling the transport.
+pub trait HttpTransport: fmt::Debug + Send + Sync + 'static {
+    /// Send `request`, and return the response without reading its body.
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_>;
+}
+
+/// The default `HttpTransport`, which sends requests using `reqwest`.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Create a new transport which sends requests using `client`.
+    pub fn new(client: reqwest::Client) -> ReqwestTransport {
+        ReqwestTransport { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(
+            self.client
+                .execute(request)
+                .map(|result| result.map_err(TransportError::from)),
+        )
+    }
+}
+
+#[test]
+#[cfg(feature = "timeseries")]
+fn clients_send_requests_using_custom_transports() {
+    use futures::{executor::block_on, future};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    use crate::resource::{Forecast, Id};
+    use crate::Client;
+
+    /// Returns the same forecast for every request, and remembers the paths
+    /// it was asked for.
+    #[derive(Debug, Default)]
+    struct FakeTransport {
+        paths: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HttpTransport for FakeTransport {
+        fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+            self.paths
+                .lock()
+                .unwrap()
+                .push(request.url().path().to_owned());
+            let body = json!({
+                "category": 0,
+                "code": 200,
+                "description": "",
+                "name": "sales",
+                "shared": false,
+                "subscription": false,
+                "tags": [],
+                "resource": "forecast/5e5e5e5e5e5e5e5e5e5e5e5e",
+                "status": { "code": 5, "message": "The forecast has been created" },
+                "timeseries": "timeseries/5e5e5e5e5e5e5e5e5e5e5e5e",
+            });
+            let res = http::Response::builder()
+                .header("content-type", "application/json")
+                .body(body.to_string())
+                .unwrap();
+            future::ready(Ok(reqwest::Response::from(res))).boxed()
+        }
+    }
+
+    let transport = FakeTransport::default();
+    let paths = transport.paths.clone();
+    let client = Client::builder("user", "key")
+        .transport(transport)
+        .build()
+        .unwrap();
+    let id: Id<Forecast> = "forecast/5e5e5e5e5e5e5e5e5e5e5e5e".parse().unwrap();
+    let forecast = block_on(client.fetch(&id)).unwrap();
+    assert_eq!(forecast.common.name, "sales");
+    assert_eq!(
+        *paths.lock().unwrap(),
+        vec!["/forecast/5e5e5e5e5e5e5e5e5e5e5e5e".to_owned()],
+    );
+}
@@ -1,16 +1,18 @@
 //! Utilities for waiting, timeouts and error retries.
 
 use std::{
-    cmp::max,
-    fmt::Display,
+    cmp::{max, min},
+    collections::VecDeque,
+    fmt::{self, Display},
     future::Future,
+    sync::{Arc, Mutex},
     time::{Duration, SystemTime},
 };
-use tokio::time::sleep;
-use tracing::{error, instrument, trace, trace_span};
+use tracing::{debug, error, instrument, trace, trace_span};
 use tracing_futures::Instrument;
 
 use crate::errors::*;
+use crate::time::{sleep, system_now, Instant};
 
 /// Minimum sleep time recommended by BigML support to avoid ban.
 const MIN_SLEEP_SECS: u64 = 4;
@@ -25,6 +27,200 @@ pub enum BackoffType {
     Exponential,
 }
 
+/// A fixed point in time after which we should stop waiting. Unlike
+/// `WaitOptions::timeout`, which starts counting when each `wait` begins, a
+/// `Deadline` can be shared by several `wait` calls, so that an entire
+/// pipeline has a single cutoff:
+///
+/// ```
+/// use std::time::Duration;
+/// use bigml::wait::{Deadline, WaitOptions};
+///
+/// let deadline = Deadline::after(Duration::from_secs(60 * 60));
+/// let source_options = WaitOptions::default().deadline(deadline);
+/// let dataset_options = WaitOptions::default().deadline(deadline);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Deadline(SystemTime);
+
+impl Deadline {
+    /// A deadline at the specified time.
+    pub fn at(time: SystemTime) -> Deadline {
+        Deadline(time)
+    }
+
+    /// A deadline `duration` from now.
+    pub fn after(duration: Duration) -> Deadline {
+        Deadline(system_now() + duration)
+    }
+
+    /// The time at which this deadline expires.
+    pub fn time(self) -> SystemTime {
+        self.0
+    }
+
+    /// How much time is left before this deadline? Returns zero if the
+    /// deadline has passed.
+    pub fn remaining(self) -> Duration {
+        self.0
+            .duration_since(system_now())
+            .unwrap_or_else(|_| Duration::from_secs(0))
+    }
+
+    /// Has this deadline already passed?
+    pub fn has_passed(self) -> bool {
+        system_now() >= self.0
+    }
+}
+
+impl From<SystemTime> for Deadline {
+    fn from(time: SystemTime) -> Self {
+        Deadline(time)
+    }
+}
+
+/// A limit on the total number of error retries allowed within a sliding time
+/// window. This can be cloned and shared by many `wait` calls (using
+/// `WaitOptions::retry_budget`), so that a systemic BigML outage makes an
+/// entire pipeline fail quickly, instead of each `wait` retrying separately:
+///
+/// ```
+/// use std::time::Duration;
+/// use bigml::wait::{RetryBudget, WaitOptions};
+///
+/// // Allow 20 retries every 10 minutes, across all our waits.
+/// let budget = RetryBudget::new(20, Duration::from_secs(10 * 60));
+/// let options = WaitOptions::default().retry_budget(budget);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryBudget {
+    inner: Arc<Mutex<RetryBudgetState>>,
+}
+
+/// The shared state of a `RetryBudget`.
+#[derive(Debug)]
+struct RetryBudgetState {
+    /// The maximum number of retries per `window`.
+    max_retries: usize,
+    /// The length of our sliding window.
+    window: Duration,
+    /// When did we spend each retry which is still inside our window?
+    spent: VecDeque<Instant>,
+}
+
+impl RetryBudgetState {
+    /// Forget about any retries which fall outside our window.
+    fn expire(&mut self, now: Instant) {
+        while let Some(&oldest) = self.spent.front() {
+            if now.duration_since(oldest) < self.window {
+                break;
+            }
+            self.spent.pop_front();
+        }
+    }
+}
+
+impl RetryBudget {
+    /// Allow up to `max_retries` retries in any period of length `window`.
+    pub fn new(max_retries: usize, window: Duration) -> RetryBudget {
+        RetryBudget {
+            inner: Arc::new(Mutex::new(RetryBudgetState {
+                max_retries,
+                window,
+                spent: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Try to spend one retry. Returns `false` if the budget is exhausted.
+    pub fn try_spend(&self) -> bool {
+        let now = Instant::now();
+        let mut state = self.inner.lock().expect("lock poisoned");
+        state.expire(now);
+        if state.spent.len() < state.max_retries {
+            state.spent.push_back(now);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How many retries are left in the current window?
+    pub fn remaining(&self) -> usize {
+        let mut state = self.inner.lock().expect("lock poisoned");
+        state.expire(Instant::now());
+        state.max_retries - state.spent.len()
+    }
+}
+
+/// Information about a `wait` in progress, passed to the hooks specified by
+/// `WaitOptions::on_retry` and `WaitOptions::on_error`.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct RetryInfo {
+    /// The number of the attempt which just finished, starting at 1.
+    pub attempt: u32,
+    /// The number of temporary errors we've decided to retry so far. When
+    /// passed to `on_error`, this includes the current error only if we're
+    /// going to retry it.
+    pub errors_seen: u16,
+    /// How long we expect to wait before the next attempt.
+    pub next_interval: Duration,
+}
+
+/// Statistics about a finished wait, returned by
+/// `Client::wait_opt_with_stats`.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct WaitStats {
+    /// The number of attempts we made, including the final one.
+    pub attempts: u32,
+    /// The number of temporary errors we retried.
+    pub temporary_errors: u16,
+    /// How long the wait took.
+    pub elapsed: Duration,
+    /// The retry interval we would have used next, after any backoff.
+    pub final_retry_interval: Duration,
+}
+
+/// A hook called by `wait` before sleeping.
+pub type RetryHook = dyn Fn(&RetryInfo) + Send + Sync;
+
+/// A hook called by `wait` when it sees a temporary error.
+pub type ErrorHook = dyn Fn(&RetryInfo, &dyn Display) + Send + Sync;
+
+/// What should we do about an error? Returned by a `RetryClassifier`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum RetryDecision {
+    /// Treat the error as temporary, and retry if our error allowances and
+    /// retry budget permit.
+    Retry,
+    /// Treat the error as permanent, and give up.
+    GiveUp,
+    /// Use our normal rules, which are based on `Error::might_be_temporary`.
+    Default,
+}
+
+/// A hook which decides whether `wait` should retry an `Error`.
+pub type RetryClassifier = dyn Fn(&Error) -> RetryDecision + Send + Sync;
+
+/// A shareable hook function. This exists so that `WaitOptions` can implement
+/// `Clone` and `Debug`.
+pub(crate) struct Hook<F: ?Sized>(pub(crate) Arc<F>);
+
+impl<F: ?Sized> Clone for Hook<F> {
+    fn clone(&self) -> Self {
+        Hook(self.0.clone())
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Hook<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hook(..)")
+    }
+}
+
 /// Options controlling how long we wait and what makes us give up.
 /// This uses a "builder" pattern, so you can write:
 ///
@@ -36,19 +232,52 @@ pub enum BackoffType {
 ///     .timeout(Duration::from_secs(120))
 ///     .allowed_errors(5);
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WaitOptions {
     /// Time between each retry.
     timeout: Option<Duration>,
 
+    /// An absolute deadline, possibly shared with other operations.
+    deadline: Option<Deadline>,
+
     /// How long to wait between retries.
     retry_interval: Duration,
 
     /// What kind of back-off should we use?
     backoff_type: BackoffType,
 
+    /// The longest we should ever wait between retries.
+    max_retry_interval: Option<Duration>,
+
     /// How many errors are we allowed before giving up?
     allowed_errors: u16,
+
+    /// How many network errors are we allowed, if counted separately?
+    allowed_network_errors: Option<u16>,
+
+    /// How many temporary BigML API errors are we allowed, if counted
+    /// separately?
+    allowed_api_errors: Option<u16>,
+
+    /// How much should we randomly vary each sleep, as a fraction of the
+    /// retry interval?
+    jitter: f32,
+
+    /// A retry budget shared with other operations.
+    retry_budget: Option<RetryBudget>,
+
+    /// How long should BigML wait server-side for a resource to become
+    /// ready before answering each poll?
+    server_wait: Option<Duration>,
+
+    /// Called before we sleep.
+    on_retry: Option<Hook<RetryHook>>,
+
+    /// Called when we see a temporary error.
+    on_error: Option<Hook<ErrorHook>>,
+
+    /// Overrides our normal choice of which errors to retry.
+    retry_classifier: Option<Hook<RetryClassifier>>,
 }
 
 impl WaitOptions {
@@ -58,6 +287,33 @@ impl WaitOptions {
         self
     }
 
+    /// Set an optional absolute deadline after which to abandon this `wait`.
+    /// If both a `timeout` and a `deadline` are specified, whichever comes
+    /// first wins.
+    pub fn deadline<D: Into<Option<Deadline>>>(mut self, deadline: D) -> Self {
+        self.deadline = deadline.into();
+        self
+    }
+
+    /// Return a copy of these options, with `timeout` converted to a
+    /// `deadline` starting now. This allows a sequence of waits to share a
+    /// single timeout.
+    pub(crate) fn with_fixed_deadline(&self) -> WaitOptions {
+        self.to_owned()
+            .timeout(None)
+            .deadline(self.effective_deadline())
+    }
+
+    /// Return the deadline for a `wait` starting now, taking into account
+    /// both `timeout` and `deadline`.
+    fn effective_deadline(&self) -> Option<Deadline> {
+        let from_timeout = self.timeout.map(Deadline::after);
+        match (from_timeout, self.deadline) {
+            (Some(a), Some(b)) => Some(min(a, b)),
+            (a, b) => a.or(b),
+        }
+    }
+
     /// How long should we wait between retries? Defaults to 10 seconds. Note
     /// that BigML has suggested not polling more often than every 4 seconds,
     /// (to avoid losing API access) so if you set a lower value, this will be
@@ -72,6 +328,16 @@ impl WaitOptions {
         self
     }
 
+    /// Never wait longer than `interval` between retries, even when using
+    /// exponential backoff. By default, there is no limit.
+    pub fn max_retry_interval<D: Into<Option<Duration>>>(
+        mut self,
+        interval: D,
+    ) -> Self {
+        self.max_retry_interval = interval.into();
+        self
+    }
+
     /// How many errors should be ignored before giving up? This can be useful
     /// for long-running `Execution` jobs, where we don't want a transient
     /// network error to result in failure.
@@ -79,15 +345,246 @@ impl WaitOptions {
         self.allowed_errors = count;
         self
     }
+
+    /// How many network errors, such as connection failures and timeouts,
+    /// should be ignored before giving up? If this is set, these errors are
+    /// counted separately instead of against `allowed_errors`. Network errors
+    /// are usually harmless to retry, so this can be fairly high.
+    pub fn allowed_network_errors<N: Into<Option<u16>>>(mut self, count: N) -> Self {
+        self.allowed_network_errors = count.into();
+        self
+    }
+
+    /// How many temporary BigML API errors, such as "payment required" or
+    /// "service unavailable", should be ignored before giving up? If this is
+    /// set, these errors are counted separately instead of against
+    /// `allowed_errors`.
+    pub fn allowed_api_errors<N: Into<Option<u16>>>(mut self, count: N) -> Self {
+        self.allowed_api_errors = count.into();
+        self
+    }
+
+    /// Which counter should we use for an error of `kind`, and how many
+    /// errors does it allow?
+    fn allowance(&self, kind: Option<TemporaryErrorKind>) -> (ErrorCounter, u16) {
+        match (kind, self.allowed_network_errors, self.allowed_api_errors) {
+            (Some(TemporaryErrorKind::Network), Some(allowed), _) => {
+                (ErrorCounter::Network, allowed)
+            }
+            (Some(TemporaryErrorKind::Api), _, Some(allowed)) => {
+                (ErrorCounter::Api, allowed)
+            }
+            _ => (ErrorCounter::General, self.allowed_errors),
+        }
+    }
+
+    /// Only retry temporary errors if `budget` allows it, in addition to
+    /// honoring `allowed_errors`. A single budget can be shared by many
+    /// waits.
+    pub fn retry_budget<B: Into<Option<RetryBudget>>>(mut self, budget: B) -> Self {
+        self.retry_budget = budget.into();
+        self
+    }
+
+    /// Randomly vary each sleep by up to `jitter` times the retry interval,
+    /// in either direction. This should be between 0.0 (the default) and 1.0;
+    /// other finite values are clamped to that range, and infinite or NaN
+    /// values disable jitter.
+    /// Adding jitter prevents many tasks from polling BigML in lockstep after
+    /// a shared failure.
+    pub fn jitter(mut self, jitter: f32) -> Self {
+        self.jitter = if jitter.is_finite() {
+            jitter.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self
+    }
+
+    /// When polling a resource, ask BigML to hold each request open for up
+    /// to `wait_time`, returning early if the resource becomes ready. This
+    /// can drastically cut the number of requests needed to wait for slow
+    /// resources. By default, we don't ask BigML to wait.
+    pub fn server_wait<D: Into<Option<Duration>>>(mut self, wait_time: D) -> Self {
+        self.server_wait = wait_time.into();
+        self
+    }
+
+    /// The value set by `server_wait`.
+    pub(crate) fn server_wait_time(&self) -> Option<Duration> {
+        self.server_wait
+    }
+
+    /// Call `hook` each time we're about to sleep before retrying. This can be
+    /// used to log or report slow BigML operations.
+    pub fn on_retry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RetryInfo) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Hook(Arc::new(hook)));
+        self
+    }
+
+    /// Call `hook` each time we see a temporary error, whether or not we're
+    /// going to retry. This can be used to alert on degraded BigML behavior
+    /// before we finally give up.
+    ///
+    /// ```
+    /// use bigml::wait::WaitOptions;
+    ///
+    /// let options = WaitOptions::default().on_error(|info, err| {
+    ///     eprintln!("attempt {} failed: {}", info.attempt, err);
+    /// });
+    /// ```
+    pub fn on_error<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RetryInfo, &dyn Display) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Hook(Arc::new(hook)));
+        self
+    }
+
+    /// Use `classifier` to decide which errors to retry, overriding
+    /// `Error::might_be_temporary` unless it returns `RetryDecision::Default`.
+    /// Errors may be wrapped in other errors, so you'll usually want to look
+    /// at `Error::original_bigml_error`.
+    ///
+    /// The classifier is asked about every failure seen by `Client`'s own
+    /// waits (such as `Client::wait`, `Client::wait_stream`,
+    /// `Client::wait_all` and `Client::download`) and by `wait_classified`.
+    /// This includes errors fetching a resource, which may be given up on
+    /// using `RetryDecision::GiveUp`, and resources whose status says they
+    /// failed, which may be polled again using `RetryDecision::Retry`. It isn't
+    /// used by `wait`, which knows nothing about its error type.
+    ///
+    /// ```
+    /// use bigml::{wait::{RetryDecision, WaitOptions}, Error};
+    ///
+    /// let options = WaitOptions::default().retry_classifier(|err: &Error| {
+    ///     match err.original_bigml_error() {
+    ///         Error::WaitFailed { error_code: Some(-1200), .. } => RetryDecision::Retry,
+    ///         _ => RetryDecision::Default,
+    ///     }
+    /// });
+    /// ```
+    pub fn retry_classifier<F>(mut self, classifier: F) -> Self
+    where
+        F: Fn(&Error) -> RetryDecision + Send + Sync + 'static,
+    {
+        self.retry_classifier = Some(Hook(Arc::new(classifier)));
+        self
+    }
+
+    /// Use `classifier` unless we already have a retry classifier.
+    pub(crate) fn with_default_retry_classifier(
+        mut self,
+        classifier: Option<&Hook<RetryClassifier>>,
+    ) -> Self {
+        if self.retry_classifier.is_none() {
+            self.retry_classifier = classifier.cloned();
+        }
+        self
+    }
+
+    /// Ask our retry classifier (if any) what to do about `err`.
+    pub(crate) fn classify(&self, err: &Error) -> RetryDecision {
+        match &self.retry_classifier {
+            Some(Hook(classifier)) => classifier(err),
+            None => RetryDecision::Default,
+        }
+    }
+
+    /// Create `WaitOptions` from the default values, overridden by the
+    /// environment variables described in `WaitOptions::with_env`.
+    pub fn from_env() -> Result<Self> {
+        WaitOptions::default().with_env()
+    }
+
+    /// Override these options using any of the following environment
+    /// variables which are set:
+    ///
+    /// - `BIGML_RETRY_INTERVAL`: The initial retry interval, in seconds.
+    /// - `BIGML_ALLOWED_ERRORS`: The number of temporary errors to allow.
+    /// - `BIGML_TIMEOUT`: The timeout, in seconds.
+    ///
+    /// This is used by `Client::new_from_env`, so that polling can be tuned
+    /// without code changes.
+    pub fn with_env(self) -> Result<Self> {
+        self.with_vars(|var| std::env::var(var).ok())
+    }
+
+    /// Like `with_env`, but looking up variables using `lookup`, so that we
+    /// can test it without touching the real environment.
+    fn with_vars<F>(mut self, lookup: F) -> Result<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(value) = lookup("BIGML_RETRY_INTERVAL") {
+            self.retry_interval = parse_secs("BIGML_RETRY_INTERVAL", &value)?;
+        }
+        if let Some(value) = lookup("BIGML_ALLOWED_ERRORS") {
+            self.allowed_errors = value.parse().map_err(|err| {
+                Error::could_not_parse_env_var("BIGML_ALLOWED_ERRORS", &value, err)
+            })?;
+        }
+        if let Some(value) = lookup("BIGML_TIMEOUT") {
+            self.timeout = Some(parse_secs("BIGML_TIMEOUT", &value)?);
+        }
+        Ok(self)
+    }
+
+    /// Given the current `retry_interval`, what should the next one be?
+    fn next_retry_interval(&self, retry_interval: Duration) -> Duration {
+        let next = match self.backoff_type {
+            BackoffType::Linear => retry_interval,
+            BackoffType::Exponential => retry_interval * 2,
+        };
+        match self.max_retry_interval {
+            Some(max_retry_interval) => min(next, max_retry_interval),
+            None => next,
+        }
+    }
+
+    /// How long should we sleep before our next attempt, given the current
+    /// `retry_interval`?
+    fn sleep_duration(&self, retry_interval: Duration) -> Duration {
+        let interval = apply_jitter(retry_interval, self.jitter, rand::random());
+        max(Duration::from_secs(MIN_SLEEP_SECS), interval)
+    }
+}
+
+/// Parse `value`, the value of the environment variable `var`, as a number of
+/// seconds.
+fn parse_secs(var: &str, value: &str) -> Result<Duration> {
+    value
+        .parse()
+        .map(Duration::from_secs)
+        .map_err(|err| Error::could_not_parse_env_var(var, value, err))
+}
+
+/// Scale `interval` by a factor between `1.0 - jitter` and `1.0 + jitter`,
+/// chosen using `random`, which should be between 0.0 and 1.0.
+fn apply_jitter(interval: Duration, jitter: f32, random: f32) -> Duration {
+    interval.mul_f32(1.0 + jitter * (2.0 * random - 1.0))
 }
 
 impl Default for WaitOptions {
     fn default() -> Self {
         Self {
             timeout: None,
+            deadline: None,
             retry_interval: Duration::from_secs(10),
             backoff_type: BackoffType::Linear,
+            max_retry_interval: None,
             allowed_errors: 2,
+            allowed_network_errors: None,
+            allowed_api_errors: None,
+            jitter: 0.0,
+            retry_budget: None,
+            server_wait: None,
+            on_retry: None,
+            on_error: None,
+            retry_classifier: None,
         }
     }
 }
@@ -174,75 +671,441 @@ macro_rules! try_with_permanent_failure {
 ///
 /// If you return `Ok(WaitStatus::Waiting)` instead, this function will wait
 /// some number of seconds, and then try again.
+///
+/// Since `wait` knows nothing about `E`, every temporary error counts against
+/// `WaitOptions::allowed_errors`, and `WaitOptions::retry_classifier` is not
+/// used. If `f` fails with `bigml::Error`, use `wait_classified` instead.
 #[allow(clippy::needless_lifetimes)]
 #[instrument(level = "trace", skip(f))]
-pub async fn wait<T, E, F, R>(options: &WaitOptions, mut f: F) -> Result<T, E>
+pub async fn wait<T, E, F, R>(options: &WaitOptions, f: F) -> Result<T, E>
+where
+    F: FnMut() -> R,
+    R: Future<Output = WaitStatus<T, E>>,
+    E: Display,
+    Error: Into<E>,
+{
+    wait_helper(options, f, |_| (RetryDecision::Default, None)).await
+}
+
+/// Like `wait`, but for functions which fail with `bigml::Error`. Temporary
+/// network and API errors may be counted against
+/// `WaitOptions::allowed_network_errors` and
+/// `WaitOptions::allowed_api_errors`, and `WaitOptions::retry_classifier` is
+/// asked about every failure, including permanent ones.
+#[instrument(level = "trace", skip(f))]
+pub async fn wait_classified<T, F, R>(options: &WaitOptions, f: F) -> Result<T>
+where
+    F: FnMut() -> R,
+    R: Future<Output = WaitStatus<T, Error>>,
+{
+    wait_helper(options, f, |err| {
+        (options.classify(err), err.temporary_kind())
+    })
+    .await
+}
+
+/// Shared implementation of `wait` and `wait_classified`. `classify` tells us
+/// what our retry classifier thinks of an error, and which allowance it
+/// should be counted against.
+async fn wait_helper<T, E, F, R, C>(
+    options: &WaitOptions,
+    mut f: F,
+    classify: C,
+) -> Result<T, E>
 where
     F: FnMut() -> R,
     R: Future<Output = WaitStatus<T, E>>,
     E: Display,
     Error: Into<E>,
+    C: Fn(&E) -> (RetryDecision, Option<TemporaryErrorKind>),
 {
-    let deadline = options.timeout.map(|to| SystemTime::now() + to);
-    let mut retry_interval = options.retry_interval;
-    trace!(
-        "waiting with deadline {:?}, initial interval {:?}",
-        deadline,
-        retry_interval
-    );
-    let mut errors_seen = 0;
+    let mut state = RetryState::new(options);
     loop {
         // Call the function we're waiting on.
-        let fut = f().instrument(trace_span!("wait_attempt", errors_seen));
+        let fut = f().instrument(trace_span!(
+            "wait_attempt",
+            errors_seen = state.errors_seen()
+        ));
         match fut.await {
             WaitStatus::Finished(value) => {
                 trace!("wait finished successfully");
                 return Ok(value);
             }
             WaitStatus::Waiting => trace!("waiting some more"),
-            WaitStatus::FailedTemporarily(ref e)
-                if errors_seen < options.allowed_errors =>
-            {
-                errors_seen += 1;
-                error!(
-                    "got error, will retry ({}/{}): {}",
-                    errors_seen, options.allowed_errors, e,
-                );
-            }
             WaitStatus::FailedTemporarily(err) => {
-                trace!("too many temporary failures, giving up on wait: {}", err);
-                return Err(err);
+                let (decision, kind) = classify(&err);
+                if decision == RetryDecision::GiveUp {
+                    trace!("retry classifier says to give up on wait: {}", err);
+                    return Err(err);
+                }
+                state.record_error(err, kind)?;
             }
             WaitStatus::FailedPermanently(err) => {
-                trace!("permanent failure, giving up on wait: {}", err);
-                return Err(err);
+                let (decision, kind) = classify(&err);
+                if decision == RetryDecision::Retry {
+                    state.record_error(err, kind)?;
+                } else {
+                    trace!("permanent failure, giving up on wait: {}", err);
+                    return Err(err);
+                }
             }
         }
 
+        // Sleep until our next call.
+        state.sleep().await.map_err(|err| err.into())?;
+    }
+}
+
+/// The allowances which temporary errors can be counted against.
+#[derive(Clone, Copy)]
+enum ErrorCounter {
+    General = 0,
+    Network = 1,
+    Api = 2,
+}
+
+/// The state of an in-progress `wait`, tracking our deadline, retry interval
+/// and the number of errors we've seen. This is shared by `wait` and by
+/// other code in this crate which needs to retry in a loop.
+pub(crate) struct RetryState {
+    /// The options we're using.
+    options: WaitOptions,
+    /// When must we stop trying?
+    deadline: Option<Deadline>,
+    /// How long until our next attempt?
+    retry_interval: Duration,
+    /// How many temporary errors have we retried so far?
+    errors_seen: u16,
+    /// How many errors have we counted against each allowance?
+    counted: [u16; 3],
+    /// The number of the current attempt, starting at 1.
+    attempt: u32,
+    /// When did we start?
+    started: Instant,
+}
+
+impl RetryState {
+    /// Start tracking a new wait.
+    pub(crate) fn new(options: &WaitOptions) -> RetryState {
+        let deadline = options.effective_deadline();
+        let retry_interval = options.retry_interval;
+        trace!(
+            "waiting with deadline {:?}, initial interval {:?}",
+            deadline,
+            retry_interval
+        );
+        RetryState {
+            options: options.to_owned(),
+            deadline,
+            retry_interval,
+            errors_seen: 0,
+            counted: [0; 3],
+            attempt: 1,
+            started: Instant::now(),
+        }
+    }
+
+    /// The number of the current attempt, starting at 1.
+    pub(crate) fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// How many temporary errors have we retried?
+    pub(crate) fn errors_seen(&self) -> u16 {
+        self.errors_seen
+    }
+
+    /// Record a temporary error, counting it against the allowance for
+    /// `kind`. Returns `Ok(())` if we may retry, or the original error if
+    /// we've seen too many or our retry budget is used up.
+    pub(crate) fn record_error<E>(
+        &mut self,
+        err: E,
+        kind: Option<TemporaryErrorKind>,
+    ) -> Result<(), E>
+    where
+        E: Display,
+    {
+        let (counter, allowed) = self.options.allowance(kind);
+        let counted = &mut self.counted[counter as usize];
+        let will_retry = *counted < allowed
+            && match self.options.retry_budget {
+                Some(ref budget) => budget.try_spend(),
+                None => true,
+            };
+        if will_retry {
+            *counted += 1;
+            self.errors_seen += 1;
+        }
+        let counted = *counted;
+        if let Some(Hook(ref on_error)) = self.options.on_error {
+            on_error(&self.info(self.retry_interval), &err);
+        }
+        if will_retry {
+            error!("got error, will retry ({}/{}): {}", counted, allowed, err);
+            Ok(())
+        } else {
+            trace!("too many temporary failures, giving up on wait: {}", err);
+            Err(err)
+        }
+    }
+
+    /// Record a temporary `bigml::Error`, unless our retry classifier says to
+    /// give up on it. Returns `Ok(())` if we may retry, or `err` otherwise.
+    pub(crate) fn record_bigml_error(&mut self, err: Error) -> Result<(), Error> {
+        if self.options.classify(&err) == RetryDecision::GiveUp {
+            trace!("retry classifier says to give up on wait: {}", err);
+            return Err(err);
+        }
+        let kind = err.temporary_kind();
+        self.record_error(err, kind)
+    }
+
+    /// Should we retry `err`, a permanent failure, because our retry
+    /// classifier says so? If so, this records it like a temporary error,
+    /// and returns `Ok(())` if we may retry. Otherwise it returns `err`.
+    pub(crate) fn retry_permanent_error(&mut self, err: Error) -> Result<(), Error> {
+        if self.options.classify(&err) == RetryDecision::Retry {
+            let kind = err.temporary_kind();
+            self.record_error(err, kind)
+        } else {
+            trace!("permanent failure, giving up on wait: {}", err);
+            Err(err)
+        }
+    }
+
+    /// Summarize this wait so far.
+    pub(crate) fn stats(&self) -> WaitStats {
+        WaitStats {
+            attempts: self.attempt,
+            temporary_errors: self.errors_seen,
+            elapsed: self.started.elapsed(),
+            final_retry_interval: self.retry_interval,
+        }
+    }
+
+    /// Describe our current state for a hook.
+    fn info(&self, next_interval: Duration) -> RetryInfo {
+        RetryInfo {
+            attempt: self.attempt,
+            errors_seen: self.errors_seen,
+            next_interval,
+        }
+    }
+
+    /// Sleep until our next attempt, or return `Error::Timeout` if that
+    /// attempt would fall after our deadline.
+    pub(crate) async fn sleep(&mut self) -> Result<()> {
         // Check to see if we'll exceed our deadline (if we have one).
-        if let Some(deadline) = deadline {
-            let next_attempt = SystemTime::now() + retry_interval;
-            if next_attempt > deadline {
+        let duration = self.options.sleep_duration(self.retry_interval);
+        if let Some(deadline) = self.deadline {
+            let next_attempt = system_now() + duration;
+            if next_attempt > deadline.time() {
                 trace!(
                     "next attempt {:?} would fall after deadline {:?}, ending wait",
                     next_attempt,
                     deadline
                 );
-                return Err(Error::Timeout {}.into());
+                return Err(Error::Timeout {});
             }
         }
 
         // Sleep until our next call.
-        let duration = max(Duration::from_secs(MIN_SLEEP_SECS), retry_interval);
+        debug!(
+            attempt = self.attempt,
+            errors_seen = self.errors_seen,
+            next_sleep_ms = duration.as_millis() as u64,
+            "sleeping before next attempt",
+        );
+        if let Some(Hook(ref on_retry)) = self.options.on_retry {
+            on_retry(&self.info(duration));
+        }
         sleep(duration).await;
+        self.attempt += 1;
 
         // Update retry interval.
-        match options.backoff_type {
-            BackoffType::Linear => {}
-            BackoffType::Exponential => {
-                retry_interval *= 2;
-                trace!("next retry doubled to {:?}", retry_interval);
-            }
-        }
+        self.retry_interval = self.options.next_retry_interval(self.retry_interval);
+        trace!("next retry interval is {:?}", self.retry_interval);
+        Ok(())
     }
 }
+
+#[test]
+fn jitter_stays_within_bounds() {
+    let interval = Duration::from_secs(10);
+    assert_eq!(apply_jitter(interval, 0.0, 0.9), interval);
+    assert_eq!(apply_jitter(interval, 0.5, 0.0), Duration::from_secs(5));
+    assert_eq!(apply_jitter(interval, 0.5, 0.5), interval);
+    assert_eq!(apply_jitter(interval, 0.5, 1.0), Duration::from_secs(15));
+    assert_eq!(WaitOptions::default().jitter(f32::NAN).jitter, 0.0);
+    assert_eq!(WaitOptions::default().jitter(f32::INFINITY).jitter, 0.0);
+    assert_eq!(WaitOptions::default().jitter(2.0).jitter, 1.0);
+}
+
+#[test]
+fn exponential_backoff_is_capped() {
+    let options = WaitOptions::default()
+        .backoff_type(BackoffType::Exponential)
+        .max_retry_interval(Duration::from_secs(30));
+    let interval = options.next_retry_interval(Duration::from_secs(10));
+    assert_eq!(interval, Duration::from_secs(20));
+    let interval = options.next_retry_interval(interval);
+    assert_eq!(interval, Duration::from_secs(30));
+    let interval = options.next_retry_interval(interval);
+    assert_eq!(interval, Duration::from_secs(30));
+}
+
+#[test]
+fn earliest_deadline_wins() {
+    let soon = Deadline::after(Duration::from_secs(10));
+    let options = WaitOptions::default()
+        .timeout(Duration::from_secs(60 * 60))
+        .deadline(soon);
+    assert_eq!(options.effective_deadline(), Some(soon));
+
+    let later = Deadline::after(Duration::from_secs(2 * 60 * 60));
+    let options = options.deadline(later);
+    assert!(options.effective_deadline().unwrap() < later);
+}
+
+#[test]
+fn fixed_deadline_does_not_move() {
+    let options = WaitOptions::default().timeout(Duration::from_secs(60));
+    let fixed = options.with_fixed_deadline();
+    assert!(fixed.timeout.is_none());
+    let deadline = fixed.effective_deadline().unwrap();
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(fixed.effective_deadline(), Some(deadline));
+}
+
+#[test]
+fn error_hook_sees_each_temporary_error() {
+    use std::sync::atomic::{AtomicU16, Ordering};
+
+    let seen = Arc::new(AtomicU16::new(0));
+    let seen2 = seen.clone();
+    let options =
+        WaitOptions::default()
+            .allowed_errors(1)
+            .on_error(move |info, _err| {
+                seen2.store(info.errors_seen, Ordering::SeqCst);
+            });
+    let mut state = RetryState::new(&options);
+    assert!(state.record_error("first", None).is_ok());
+    assert_eq!(seen.load(Ordering::SeqCst), 1);
+    assert_eq!(state.record_error("second", None), Err("second"));
+    assert_eq!(seen.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn override_options_from_env_vars() {
+    let vars = |var: &str| match var {
+        "BIGML_RETRY_INTERVAL" => Some("30".to_owned()),
+        "BIGML_TIMEOUT" => Some("600".to_owned()),
+        _ => None,
+    };
+    let options = WaitOptions::default()
+        .allowed_errors(5)
+        .with_vars(vars)
+        .unwrap();
+    assert_eq!(options.retry_interval, Duration::from_secs(30));
+    assert_eq!(options.timeout, Some(Duration::from_secs(600)));
+    assert_eq!(options.allowed_errors, 5);
+
+    let bad = |var: &str| match var {
+        "BIGML_ALLOWED_ERRORS" => Some("lots".to_owned()),
+        _ => None,
+    };
+    assert!(WaitOptions::default().with_vars(bad).is_err());
+}
+
+#[test]
+fn shared_retry_budget_is_exhausted() {
+    let budget = RetryBudget::new(2, Duration::from_secs(60 * 60));
+    let options = WaitOptions::default()
+        .allowed_errors(10)
+        .retry_budget(budget.clone());
+    let mut first = RetryState::new(&options);
+    let mut second = RetryState::new(&options);
+    assert!(first.record_error("oops", None).is_ok());
+    assert!(second.record_error("oops", None).is_ok());
+    assert_eq!(budget.remaining(), 0);
+    assert!(first.record_error("oops", None).is_err());
+}
+
+#[test]
+fn network_and_api_errors_have_separate_allowances() {
+    use reqwest::StatusCode;
+    use std::io;
+
+    let options = WaitOptions::default()
+        .allowed_errors(0)
+        .allowed_network_errors(2)
+        .allowed_api_errors(1);
+    let network = || Error::from(io::Error::from(io::ErrorKind::ConnectionReset));
+    let api = || Error::UnexpectedHttpStatus {
+        url: "https://bigml.io/".parse().unwrap(),
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        body: String::new(),
+        request_id: None,
+    };
+    let mut state = RetryState::new(&options);
+    assert!(state.record_bigml_error(network()).is_ok());
+    assert!(state.record_bigml_error(api()).is_ok());
+    assert!(state.record_bigml_error(network()).is_ok());
+    assert!(state.record_bigml_error(api()).is_err());
+    assert!(state.record_bigml_error(network()).is_err());
+    assert!(state.record_error("other", None).is_err());
+    assert_eq!(state.errors_seen(), 3);
+}
+
+#[test]
+fn retry_classifier_overrides_default_rules() {
+    let options = WaitOptions::default()
+        .allowed_errors(1)
+        .retry_classifier(|err| match err {
+            Error::MissingEnvVar { var } if var == "RETRY" => RetryDecision::Retry,
+            Error::PaymentRequired { .. } => RetryDecision::GiveUp,
+            _ => RetryDecision::Default,
+        });
+
+    let mut state = RetryState::new(&options);
+    let payment_required = Error::PaymentRequired {
+        url: "https://bigml.io/".parse().unwrap(),
+        body: String::new(),
+        request_id: None,
+    };
+    assert!(state.record_bigml_error(payment_required).is_err());
+    assert_eq!(state.errors_seen(), 0);
+
+    assert!(state
+        .retry_permanent_error(Error::missing_env_var("RETRY"))
+        .is_ok());
+    assert!(state
+        .retry_permanent_error(Error::missing_env_var("OTHER"))
+        .is_err());
+    assert_eq!(state.errors_seen(), 1);
+}
+
+#[test]
+fn wait_classified_retries_permanent_failures_when_asked() {
+    use futures::executor::block_on;
+
+    // With no time left, any retry turns into a timeout, so we can see which
+    // failures would have been retried without sleeping.
+    let options = WaitOptions::default()
+        .timeout(Duration::ZERO)
+        .retry_classifier(|err| match err {
+            Error::MissingEnvVar { .. } => RetryDecision::Retry,
+            _ => RetryDecision::Default,
+        });
+    let fail = || async {
+        WaitStatus::<(), _>::FailedPermanently(Error::missing_env_var("RETRY"))
+    };
+    let result = block_on(wait_classified(&options, fail));
+    assert!(matches!(result, Err(Error::Timeout { .. })));
+
+    // Plain `wait` doesn't know about our classifier.
+    let result = block_on(wait(&options, fail));
+    assert!(matches!(result, Err(Error::MissingEnvVar { .. })));
+}
@@ -0,0 +1,229 @@
+//! Uploading groups of interdependent WhizzML libraries.
+
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, instrument};
+
+use crate::client::Client;
+use crate::errors::*;
+use crate::resource::{library, Id, Library};
+
+/// A WhizzML library which has not yet been uploaded to BigML, and which may
+/// import other local libraries.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LocalLibrary {
+    /// A local name for this library, used by other libraries to import it.
+    pub key: String,
+
+    /// The arguments used to create this library. Any IDs already present in
+    /// `args.imports` will be kept, and the IDs of our `imports` will be added
+    /// after them.
+    pub args: library::Args,
+
+    /// The keys of other `LocalLibrary` values imported by this one.
+    pub imports: Vec<String>,
+}
+
+impl LocalLibrary {
+    /// Create a new `LocalLibrary` with no local imports.
+    pub fn new<S: Into<String>>(key: S, args: library::Args) -> LocalLibrary {
+        LocalLibrary {
+            key: key.into(),
+            args,
+            imports: vec![],
+        }
+    }
+
+    /// Import the local library with the specified key.
+    pub fn import<S: Into<String>>(mut self, key: S) -> Self {
+        self.imports.push(key.into());
+        self
+    }
+}
+
+/// Upload `libraries` to BigML, making sure that each library is created (and
+/// ready) before any library which imports it. Local imports are rewritten to
+/// point at the newly-created `library/...` IDs.
+///
+/// Returns a map from each `LocalLibrary::key` to the ID of the corresponding
+/// library.
+///
+/// ```no_run
+/// # use bigml::{Client, resource::library, whizzml::{upload_libraries, LocalLibrary}};
+/// # #[tokio::main]
+/// # async fn main() -> bigml::Result<()> {
+/// let client = Client::new_from_env()?;
+/// let ids = upload_libraries(&client, vec![
+///     LocalLibrary::new("app", library::Args::new("(define (f) (g))"))
+///         .import("util"),
+///     LocalLibrary::new("util", library::Args::new("(define (g) 1)")),
+/// ])
+/// .await?;
+/// println!("app is {}", ids["app"]);
+/// #   Ok(())
+/// # }
+/// ```
+#[instrument(level = "trace", skip(client, libraries))]
+pub async fn upload_libraries(
+    client: &Client,
+    libraries: Vec<LocalLibrary>,
+) -> Result<BTreeMap<String, Id<Library>>> {
+    let count = libraries.len();
+    let mut by_key = libraries
+        .into_iter()
+        .map(|library| (library.key.clone(), library))
+        .collect::<BTreeMap<_, _>>();
+    if by_key.len() != count {
+        return Err(Error::invalid_whizzml_imports(
+            "two libraries have the same key",
+        ));
+    }
+
+    let graph = by_key
+        .iter()
+        .map(|(key, library)| (key.clone(), library.imports.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let order = dependency_order(&graph).map_err(|err| match err {
+        DependencyError::Cycle(cycle) => {
+            Error::invalid_whizzml_imports(describe_cycle(&cycle, |key| key.clone()))
+        }
+        DependencyError::Missing { from, to } => Error::invalid_whizzml_imports(
+            format!("{} imports unknown library {}", from, to),
+        ),
+    })?;
+
+    let mut ids: BTreeMap<String, Id<Library>> = BTreeMap::new();
+    for key in order {
+        let mut library = by_key
+            .remove(&key)
+            .expect("dependency_order returned an unknown key");
+        for import in &library.imports {
+            library.args.imports.push(ids[import].clone());
+        }
+        let created = client.create_and_wait(&library.args).await?;
+        debug!("uploaded {} as {}", key, created.resource);
+        ids.insert(key, created.resource);
+    }
+    Ok(ids)
+}
+
+/// Errors returned by `dependency_order`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum DependencyError<K> {
+    /// A key depends on itself, either directly or indirectly. This contains
+    /// the path from that key back to itself, so the first and last keys are
+    /// the same.
+    Cycle(Vec<K>),
+    /// `from` depends on `to`, which isn't in the graph.
+    Missing { from: K, to: K },
+}
+
+/// Given a map from each key to the keys it depends on, return all the keys
+/// in an order where each key comes after all its dependencies.
+pub(crate) fn dependency_order<K>(
+    graph: &BTreeMap<K, Vec<K>>,
+) -> Result<Vec<K>, DependencyError<K>>
+where
+    K: Clone + Ord + std::hash::Hash,
+{
+    /// Depth-first helper. `done` maps each key we've encountered to `true`
+    /// once it has been added to `order`. `path` contains the keys we're
+    /// currently visiting, so that we can report cycles.
+    fn visit<K>(
+        graph: &BTreeMap<K, Vec<K>>,
+        key: &K,
+        done: &mut HashMap<K, bool>,
+        path: &mut Vec<K>,
+        order: &mut Vec<K>,
+    ) -> Result<(), DependencyError<K>>
+    where
+        K: Clone + Ord + std::hash::Hash,
+    {
+        match done.get(key) {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                let start = path
+                    .iter()
+                    .position(|k| k == key)
+                    .expect("unfinished keys should be in our path");
+                let mut cycle = path[start..].to_vec();
+                cycle.push(key.clone());
+                return Err(DependencyError::Cycle(cycle));
+            }
+            None => {}
+        }
+        done.insert(key.clone(), false);
+        path.push(key.clone());
+        for dep in &graph[key] {
+            if !graph.contains_key(dep) {
+                return Err(DependencyError::Missing {
+                    from: key.clone(),
+                    to: dep.clone(),
+                });
+            }
+            visit(graph, dep, done, path, order)?;
+        }
+        path.pop();
+        done.insert(key.clone(), true);
+        order.push(key.clone());
+        Ok(())
+    }
+
+    let mut done = HashMap::new();
+    let mut order = vec![];
+    for key in graph.keys() {
+        visit(graph, key, &mut done, &mut vec![], &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Describe a `cycle` returned in `DependencyError::Cycle`, using `name` to
+/// format each key.
+pub(crate) fn describe_cycle<K, F>(cycle: &[K], name: F) -> String
+where
+    F: Fn(&K) -> String,
+{
+    if cycle.len() <= 2 {
+        format!("{} imports itself", name(&cycle[0]))
+    } else {
+        let names = cycle.iter().map(name).collect::<Vec<_>>();
+        format!("import cycle: {}", names.join(" -> "))
+    }
+}
+
+#[test]
+fn dependency_order_sorts_and_detects_problems() {
+    let graph = |edges: &[(&'static str, &[&'static str])]| {
+        edges
+            .iter()
+            .map(|(k, deps)| (*k, deps.to_vec()))
+            .collect::<BTreeMap<_, _>>()
+    };
+
+    let ok = graph(&[("a", &["c"]), ("b", &["a"]), ("c", &[])]);
+    assert_eq!(dependency_order(&ok).unwrap(), &["c", "a", "b"]);
+
+    let cycle = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["b"])]);
+    let err = dependency_order(&cycle).unwrap_err();
+    assert_eq!(err, DependencyError::Cycle(vec!["b", "c", "b"]));
+    if let DependencyError::Cycle(cycle) = err {
+        let message = describe_cycle(&cycle, |key| key.to_string());
+        assert_eq!(message, "import cycle: b -> c -> b");
+    }
+
+    let self_import = graph(&[("a", &["a"])]);
+    let err = dependency_order(&self_import).unwrap_err();
+    assert_eq!(err, DependencyError::Cycle(vec!["a", "a"]));
+    if let DependencyError::Cycle(cycle) = err {
+        assert_eq!(
+            describe_cycle(&cycle, |key| key.to_string()),
+            "a imports itself"
+        );
+    }
+
+    let missing = graph(&[("a", &["z"])]);
+    assert_eq!(
+        dependency_order(&missing),
+        Err(DependencyError::Missing { from: "a", to: "z" }),
+    );
+}
@@ -0,0 +1,10 @@
+//! Tools for working with local WhizzML source code.
+//!
+//! The types in [`crate::resource`] describe WhizzML resources which already
+//! exist on BigML. This module helps get local source code _onto_ BigML.
+
+mod libraries;
+mod package;
+
+pub use self::libraries::{upload_libraries, LocalLibrary};
+pub use self::package::*;
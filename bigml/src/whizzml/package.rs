@@ -0,0 +1,533 @@
+//! Local WhizzML packages, in the format used by `bigmler whizzml`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component as PathComponent, Path, PathBuf};
+use tracing::{debug, instrument};
+
+use super::libraries::{dependency_order, describe_cycle, DependencyError};
+use crate::client::Client;
+use crate::errors::*;
+use crate::resource::{
+    library, script, Category, Id, Library, ResourceCommonUpdate, Script,
+};
+
+/// The name of the file describing each package component.
+const METADATA_FILE_NAME: &str = "metadata.json";
+
+/// What kind of WhizzML code is stored in a directory?
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Kind {
+    /// A WhizzML library, which may be imported by scripts and other
+    /// libraries.
+    #[serde(rename = "library")]
+    Library,
+    /// A WhizzML script, which may be executed.
+    #[serde(rename = "script")]
+    Script,
+    /// A collection of other components, listed in `components`.
+    #[serde(rename = "package")]
+    Package,
+}
+
+/// The contents of a `metadata.json` file describing a directory in a
+/// WhizzML package.
+///
+/// This uses the same format as `bigmler whizzml --package-dir`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Metadata {
+    /// What kind of directory is this?
+    pub kind: Kind,
+
+    /// The name to give the BigML resource. Defaults to the name of the
+    /// directory.
+    pub name: Option<String>,
+
+    /// A human-readable description of this component.
+    pub description: Option<String>,
+
+    /// The category which best describes this component.
+    pub category: Option<Category>,
+
+    /// The file containing the WhizzML source code, relative to this
+    /// directory. Defaults to `script.whizzml` or `library.whizzml`.
+    pub source_code: Option<String>,
+
+    /// Directories containing libraries imported by this component, relative
+    /// to this directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
+
+    /// Script input declarations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<script::Input>,
+
+    /// Script output declarations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<script::Output>,
+
+    /// For packages, the subdirectories containing each component.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+
+    /// User-defined tags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A single script or library in a [`Package`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Component {
+    /// The directory containing this component, relative to the package root.
+    pub path: PathBuf,
+
+    /// The metadata describing this component.
+    pub metadata: Metadata,
+
+    /// The WhizzML source code for this component.
+    pub source_code: String,
+
+    /// The libraries imported by this component, relative to the package
+    /// root.
+    pub imports: Vec<PathBuf>,
+}
+
+impl Component {
+    /// The name to use when creating this component on BigML.
+    pub fn name(&self) -> String {
+        self.metadata.name.clone().unwrap_or_else(|| {
+            self.path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// The BigML resources created for each component in a [`Package`], keyed by
+/// the component's directory relative to the package root (using `/` as a
+/// separator, and `.` for the root itself).
+///
+/// This can be saved as JSON and passed to a later [`Package::deploy`], which
+/// will reuse any resources that haven't changed.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Manifest {
+    /// Libraries created for this package.
+    #[serde(default)]
+    pub libraries: BTreeMap<String, Id<Library>>,
+
+    /// Scripts created for this package.
+    #[serde(default)]
+    pub scripts: BTreeMap<String, Id<Script>>,
+}
+
+/// A local directory of WhizzML scripts and libraries, which can be deployed
+/// to BigML. This is the Rust equivalent of `bigmler whizzml --package-dir`.
+///
+/// ```no_run
+/// # use bigml::{Client, whizzml::Package};
+/// # #[tokio::main]
+/// # async fn main() -> bigml::Result<()> {
+/// let client = Client::new_from_env()?;
+/// let package = Package::from_dir("my-package")?;
+/// let manifest = package.deploy(&client, None).await?;
+/// println!("{}", serde_json::to_string_pretty(&manifest)?);
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Package {
+    /// The directory containing the package.
+    root: PathBuf,
+
+    /// All the scripts and libraries in this package, keyed by path.
+    components: BTreeMap<PathBuf, Component>,
+}
+
+impl Package {
+    /// Read a package from `root`, which must contain a `metadata.json` file.
+    /// Any imported libraries will also be read, even if they aren't listed
+    /// as components.
+    #[instrument(level = "trace", skip(root), fields(root = ?root.as_ref()))]
+    pub fn from_dir<P: AsRef<Path>>(root: P) -> Result<Package> {
+        let mut package = Package {
+            root: root.as_ref().to_owned(),
+            components: BTreeMap::new(),
+        };
+        package.load(PathBuf::new(), &mut BTreeSet::new())?;
+        Ok(package)
+    }
+
+    /// The directory containing this package.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// All the scripts and libraries in this package.
+    pub fn components(&self) -> impl Iterator<Item = &Component> {
+        self.components.values()
+    }
+
+    /// Load the directory `rel_dir` (relative to our root) and anything it
+    /// depends on.
+    fn load(&mut self, rel_dir: PathBuf, seen: &mut BTreeSet<PathBuf>) -> Result<()> {
+        if !seen.insert(rel_dir.clone()) {
+            return Ok(());
+        }
+
+        let dir = self.root.join(&rel_dir);
+        let metadata_path = dir.join(METADATA_FILE_NAME);
+        let metadata: Metadata = serde_json::from_str(
+            &fs::read_to_string(&metadata_path)
+                .map_err(|err| Error::could_not_read_file(&metadata_path, err))?,
+        )
+        .map_err(|err| Error::could_not_read_file(&metadata_path, err))?;
+        debug!(
+            "loaded {:?} metadata from {:?}",
+            metadata.kind, metadata_path
+        );
+
+        match metadata.kind {
+            Kind::Package => {
+                for name in &metadata.components {
+                    let child = self.resolve(&rel_dir, name)?;
+                    self.load(child, seen)?;
+                }
+            }
+            Kind::Library | Kind::Script => {
+                let source_file = metadata.source_code.clone().unwrap_or_else(|| {
+                    match metadata.kind {
+                        Kind::Library => "library.whizzml".to_owned(),
+                        _ => "script.whizzml".to_owned(),
+                    }
+                });
+                let source_path = dir.join(source_file);
+                let source_code = fs::read_to_string(&source_path)
+                    .map_err(|err| Error::could_not_read_file(&source_path, err))?;
+                let imports = metadata
+                    .imports
+                    .iter()
+                    .map(|import| self.resolve(&rel_dir, import))
+                    .collect::<Result<Vec<_>>>()?;
+                self.components.insert(
+                    rel_dir.clone(),
+                    Component {
+                        path: rel_dir,
+                        metadata,
+                        source_code,
+                        imports: imports.clone(),
+                    },
+                );
+                for import in imports {
+                    self.load(import, seen)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve `relative` against the directory `rel_dir`, returning a
+    /// normalized path relative to our root.
+    fn resolve(&self, rel_dir: &Path, relative: &str) -> Result<PathBuf> {
+        let mut resolved = PathBuf::new();
+        for part in rel_dir.join(relative).components() {
+            match part {
+                PathComponent::CurDir => {}
+                PathComponent::ParentDir => {
+                    if !resolved.pop() {
+                        return Err(Error::invalid_whizzml_package(
+                            &self.root,
+                            format!("{:?} points outside the package", relative),
+                        ));
+                    }
+                }
+                PathComponent::Normal(name) => resolved.push(name),
+                PathComponent::RootDir | PathComponent::Prefix(_) => {
+                    return Err(Error::invalid_whizzml_package(
+                        &self.root,
+                        format!("{:?} must be a relative path", relative),
+                    ));
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Return the paths of all our components, ordered so that each library
+    /// appears before anything which imports it.
+    pub fn dependency_order(&self) -> Result<Vec<&Path>> {
+        let mut graph = BTreeMap::new();
+        for (path, component) in &self.components {
+            let mut imports = vec![];
+            for import in &component.imports {
+                match self.components.get(import) {
+                    Some(imported) if imported.metadata.kind == Kind::Library => {
+                        imports.push(imported.path.as_path());
+                    }
+                    _ => {
+                        return Err(Error::invalid_whizzml_package(
+                            &self.root,
+                            format!(
+                                "{} imports {}, which is not a library",
+                                manifest_key(path),
+                                manifest_key(import),
+                            ),
+                        ));
+                    }
+                }
+            }
+            graph.insert(path.as_path(), imports);
+        }
+        dependency_order(&graph).map_err(|err| match err {
+            DependencyError::Cycle(cycle) => Error::invalid_whizzml_package(
+                &self.root,
+                describe_cycle(&cycle, |path| manifest_key(path)),
+            ),
+            // We've already checked all our imports, above.
+            DependencyError::Missing { from, to } => Error::invalid_whizzml_package(
+                &self.root,
+                format!(
+                    "{} imports unknown {}",
+                    manifest_key(from),
+                    manifest_key(to)
+                ),
+            ),
+        })
+    }
+
+    /// Create any libraries and scripts in this package, in dependency order,
+    /// and wait for them to be ready.
+    ///
+    /// If `previous` is the manifest returned by an earlier deployment, we
+    /// reuse any resource whose source code, imports and declarations are
+    /// unchanged, updating its name if necessary. Everything else is created
+    /// from scratch, because BigML does not allow changing the source code of
+    /// an existing script.
+    #[instrument(level = "trace", skip(self, client, previous), fields(root = ?self.root))]
+    pub async fn deploy(
+        &self,
+        client: &Client,
+        previous: Option<&Manifest>,
+    ) -> Result<Manifest> {
+        let mut manifest = Manifest::default();
+        for path in self.dependency_order()? {
+            let component = &self.components[path];
+            let key = manifest_key(path);
+            let imports = component
+                .imports
+                .iter()
+                .map(|import| {
+                    manifest
+                        .libraries
+                        .get(&manifest_key(import))
+                        .cloned()
+                        .ok_or_else(|| {
+                            Error::invalid_whizzml_package(
+                                &self.root,
+                                format!("could not resolve import {:?}", import),
+                            )
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            match component.metadata.kind {
+                Kind::Library => {
+                    let previous = previous.and_then(|m| m.libraries.get(&key));
+                    let id =
+                        deploy_library(client, component, imports, previous).await?;
+                    manifest.libraries.insert(key, id);
+                }
+                Kind::Script => {
+                    let previous = previous.and_then(|m| m.scripts.get(&key));
+                    let id =
+                        deploy_script(client, component, imports, previous).await?;
+                    manifest.scripts.insert(key, id);
+                }
+                // We never store packages in `self.components`.
+                Kind::Package => unreachable!("packages are not components"),
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// Create or reuse a library.
+async fn deploy_library(
+    client: &Client,
+    component: &Component,
+    imports: Vec<Id<Library>>,
+    previous: Option<&Id<Library>>,
+) -> Result<Id<Library>> {
+    let name = component.name();
+    if let Some(id) = previous {
+        if let Some(existing) = fetch_if_exists(client, id).await? {
+            if existing.source_code == component.source_code
+                && same_ids(&existing.imports, &imports)
+            {
+                if existing.common.name != name {
+                    let update = library::LibraryUpdate {
+                        common: Some(name_update(name)),
+                        ..library::LibraryUpdate::default()
+                    };
+                    client.update(id, &update).await?;
+                }
+                debug!("reusing {} for {:?}", id, component.path);
+                return Ok(id.to_owned());
+            }
+        }
+    }
+
+    let metadata = &component.metadata;
+    let mut args = library::Args::new(component.source_code.clone());
+    args.category = metadata.category;
+    args.description = metadata.description.clone();
+    args.imports = imports;
+    args.name = Some(name);
+    args.tags = metadata.tags.clone();
+    let library = client.create_and_wait(&args).await?;
+    Ok(library.resource)
+}
+
+/// Create or reuse a script.
+async fn deploy_script(
+    client: &Client,
+    component: &Component,
+    imports: Vec<Id<Library>>,
+    previous: Option<&Id<Script>>,
+) -> Result<Id<Script>> {
+    let name = component.name();
+    let metadata = &component.metadata;
+    if let Some(id) = previous {
+        if let Some(existing) = fetch_if_exists(client, id).await? {
+            let same_inputs = existing
+                .inputs
+                .iter()
+                .map(|i| (&i.name, i.type_))
+                .eq(metadata.inputs.iter().map(|i| (&i.name, i.type_)));
+            let same_outputs = existing
+                .outputs
+                .iter()
+                .map(|o| (&o.name, o.type_))
+                .eq(metadata.outputs.iter().map(|o| (&o.name, o.type_)));
+            if existing.source_code == component.source_code
+                && same_ids(&existing.imports, &imports)
+                && same_inputs
+                && same_outputs
+            {
+                if existing.common.name != name {
+                    let update = script::ScriptUpdate {
+                        common: Some(name_update(name)),
+                        ..script::ScriptUpdate::default()
+                    };
+                    client.update(id, &update).await?;
+                }
+                debug!("reusing {} for {:?}", id, component.path);
+                return Ok(id.to_owned());
+            }
+        }
+    }
+
+    let mut args = script::Args::new(component.source_code.clone());
+    args.category = metadata.category;
+    args.description = metadata.description.clone();
+    args.imports = imports;
+    args.inputs = metadata.inputs.clone();
+    args.name = Some(name);
+    args.outputs = metadata.outputs.clone();
+    args.tags = metadata.tags.clone();
+    let script = client.create_and_wait(&args).await?;
+    Ok(script.resource)
+}
+
+/// Fetch `id`, returning `None` if it has been deleted.
+async fn fetch_if_exists<R>(client: &Client, id: &Id<R>) -> Result<Option<R>>
+where
+    R: crate::resource::Resource,
+{
+    match client.fetch(id).await {
+        Ok(resource) => Ok(Some(resource)),
+        Err(err) => match err.original_bigml_error() {
+            Error::UnexpectedHttpStatus { status, .. }
+                if *status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                Ok(None)
+            }
+            _ => Err(err),
+        },
+    }
+}
+
+/// Do two lists contain the same IDs in the same order?
+fn same_ids(a: &[Id<Library>], b: &[Id<Library>]) -> bool {
+    a.iter().map(Id::as_str).eq(b.iter().map(Id::as_str))
+}
+
+/// Build an update which renames a resource.
+fn name_update(name: String) -> ResourceCommonUpdate {
+    ResourceCommonUpdate {
+        name: Some(name),
+        ..ResourceCommonUpdate::default()
+    }
+}
+
+/// Convert a path relative to the package root into a `Manifest` key.
+fn manifest_key(path: &Path) -> String {
+    let parts = path
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        ".".to_owned()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[test]
+fn load_package_in_dependency_order() {
+    let root = std::env::temp_dir()
+        .join(format!("bigml-whizzml-package-{}", std::process::id()));
+    let write = |path: &str, contents: &str| {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+    write(
+        "metadata.json",
+        r#"{"kind": "package", "components": ["run", "./lib/b"]}"#,
+    );
+    write(
+        "run/metadata.json",
+        r#"{"kind": "script", "imports": ["../lib/b"],
+            "inputs": [{"name": "n", "type": "integer"}]}"#,
+    );
+    write("run/script.whizzml", "(define out (f n))");
+    write(
+        "lib/b/metadata.json",
+        r#"{"kind": "library", "name": "B", "imports": ["../a"]}"#,
+    );
+    write("lib/b/library.whizzml", "(define (f n) (g n))");
+    write(
+        "lib/a/metadata.json",
+        r#"{"kind": "library", "source_code": "a.whizzml"}"#,
+    );
+    write("lib/a/a.whizzml", "(define (g n) n)");
+
+    let package = Package::from_dir(&root).unwrap();
+    let order = package
+        .dependency_order()
+        .unwrap()
+        .into_iter()
+        .map(manifest_key)
+        .collect::<Vec<_>>();
+    assert_eq!(order, &["lib/a", "lib/b", "run"]);
+    let names = package.components().map(|c| c.name()).collect::<Vec<_>>();
+    assert_eq!(names, &["a", "B", "run"]);
+
+    fs::remove_dir_all(&root).unwrap();
+}
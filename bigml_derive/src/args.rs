@@ -0,0 +1,167 @@
+//! Implementation of `#[derive(Args)]`.
+
+// In this macro, we want `proc_macro2::TokenStream` to manipulate the AST using
+// high-level APIs.
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::{
+    Attribute, Data, DeriveInput, Error, Field, GenericArgument, Lit, Meta,
+    MetaNameValue, NestedMeta, PathArguments, Result, Type,
+};
+
+/// Do the actual code generation for an `Args` type.
+pub(crate) fn derive(ast: &DeriveInput) -> Result<TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let resource = get_resource_type(&ast.attrs)?;
+    let setters = setters(ast)?;
+    Ok(quote! {
+        impl #impl_generics crate::resource::Args for #name #ty_generics #where_clause {
+            type Resource = #resource;
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #setters )*
+        }
+    })
+}
+
+/// Search for an `#[args(resource = "MyResource")]` attribute and return
+/// `MyResource` as a `Type`.
+fn get_resource_type(attrs: &[Attribute]) -> Result<Type> {
+    let mut resource = None;
+    for attr in attrs {
+        if !attr.path.is_ident("args") {
+            continue;
+        }
+        let invalid = || Error::new_spanned(attr, INVALID_ARGS);
+        let options = match attr.parse_meta().map_err(|_| invalid())? {
+            Meta::List(list) => list.nested,
+            _ => return Err(invalid()),
+        };
+        for option in options {
+            match option {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("resource") && resource.is_none() => {
+                    resource = Some(lit.parse()?);
+                }
+                other => {
+                    return Err(Error::new_spanned(
+                        other,
+                        "unexpected option in `#[args(..)]`",
+                    ));
+                }
+            }
+        }
+    }
+    resource.ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "missing attribute `args`, try `#[args(resource = \"MyResource\")]`",
+        )
+    })
+}
+
+/// The error we report for a malformed `args` attribute.
+const INVALID_ARGS: &str = "invalid `args`, try `#[args(resource = \"MyResource\")]`";
+
+/// Generate a `with_*` setter for each field not marked `#[args(skip)]`.
+fn setters(ast: &DeriveInput) -> Result<Vec<TokenStream>> {
+    let data_struct = match ast.data {
+        Data::Struct(ref data_struct) => data_struct,
+        _ => {
+            return Err(Error::new_spanned(
+                &ast.ident,
+                "`#[derive(Args)]` may only be used on structs",
+            ));
+        }
+    };
+
+    let mut setters = vec![];
+    for field in &data_struct.fields {
+        if skip_field(field)? {
+            continue;
+        }
+        let name = field.ident.as_ref().ok_or_else(|| {
+            Error::new_spanned(field, "cannot `#[derive(Args)]` for tuple struct")
+        })?;
+        let setter = Ident::new(&format!("with_{}", name), name.span());
+        let comment = format!("Set `{}`.", name);
+        setters.push(match option_inner_type(&field.ty) {
+            Some(inner) => quote! {
+                #[doc = #comment]
+                pub fn #setter<V: Into<#inner>>(mut self, value: V) -> Self {
+                    self.#name = Some(value.into());
+                    self
+                }
+            },
+            None => {
+                let ty = &field.ty;
+                quote! {
+                    #[doc = #comment]
+                    pub fn #setter<V: Into<#ty>>(mut self, value: V) -> Self {
+                        self.#name = value.into();
+                        self
+                    }
+                }
+            }
+        });
+    }
+    Ok(setters)
+}
+
+/// Is this field marked with `#[args(skip)]`?
+fn skip_field(field: &Field) -> Result<bool> {
+    let mut skip = false;
+    for attr in &field.attrs {
+        if !attr.path.is_ident("args") {
+            continue;
+        }
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for option in list.nested {
+                    match option {
+                        NestedMeta::Meta(Meta::Path(ref path))
+                            if path.is_ident("skip") =>
+                        {
+                            skip = true;
+                        }
+                        other => {
+                            return Err(Error::new_spanned(
+                                other,
+                                "unexpected option in `#[args(..)]`",
+                            ));
+                        }
+                    }
+                }
+            }
+            other => {
+                return Err(Error::new_spanned(other, "expected `#[args(skip)]`"));
+            }
+        }
+    }
+    Ok(skip)
+}
+
+/// If `ty` looks like `Option<T>`, return `T`.
+pub(crate) fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(GenericArgument::Type(inner)) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -11,27 +11,49 @@ extern crate syn;
 // In this file, we want `proc_macro::TokenStream` to interface with the outside
 // world.
 use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
 
+mod args;
+mod list_filter;
 mod resource;
 mod updatable;
 
+/// Derive an `Args` implementation and `with_*` setters for each field.
+#[proc_macro_derive(Args, attributes(args))]
+pub fn args_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    args::derive(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// Derive boilerplate code for `Resource`.
-#[proc_macro_derive(Resource, attributes(api_name))]
+#[proc_macro_derive(Resource, attributes(api_name, filterable, resource))]
 pub fn resource_derive(input: TokenStream) -> TokenStream {
-    // Rust procedural macros are really limited right now:
-    //
-    // - We can only parse `TokenStream` using the `syn` library. This is
-    //   because `TokenStream` hasn't been fully standardized.
-    // - We can only report errors via a panic.
-    let input = syn::parse(input).unwrap();
-    let gen = resource::derive(&input);
-    gen.into()
+    // We can only parse `TokenStream` using the `syn` library, because
+    // `TokenStream` hasn't been fully standardized. Errors are reported by
+    // emitting `compile_error!` invocations attached to the offending tokens.
+    let input = parse_macro_input!(input as DeriveInput);
+    resource::derive(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Pass the variant name and API name of each BigML resource type we know
+/// about to the `macro_rules!` macro named in `input`. `bigml` uses this to
+/// declare `ResourceType`, so that we only have one list of resource types.
+#[doc(hidden)]
+#[proc_macro]
+pub fn known_resource_types(input: TokenStream) -> TokenStream {
+    let callback = parse_macro_input!(input as syn::Ident);
+    resource::known_resource_types(&callback).into()
 }
 
 /// Derive boilerplate code for `Updatable`.
 #[proc_macro_derive(Updatable, attributes(updatable))]
 pub fn updatable_derive(input: TokenStream) -> TokenStream {
-    let input = syn::parse(input).unwrap();
-    let gen = updatable::derive(&input);
-    gen.into()
+    let input = parse_macro_input!(input as DeriveInput);
+    updatable::derive(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
@@ -0,0 +1,162 @@
+//! Generating `<Name>ListFilter` types for `#[derive(Resource)]`.
+
+// In this macro, we want `proc_macro2::TokenStream` to manipulate the AST using
+// high-level APIs.
+use proc_macro2::{Ident, TokenStream};
+use syn::{Data, DeriveInput, Error, Meta, Result, Type};
+
+use crate::args::option_inner_type;
+
+/// Numeric types which BigML can compare using `__gt`, `__lt` and so on.
+const NUMERIC_TYPES: &[&str] = &[
+    "f32", "f64", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "usize",
+];
+
+/// If any fields are marked `#[filterable]`, generate a `<Name>ListFilter`
+/// type which can only be used to list this resource.
+pub(crate) fn derive(ast: &DeriveInput) -> Result<TokenStream> {
+    let fields = match ast.data {
+        Data::Struct(ref data_struct) => &data_struct.fields,
+        _ => return Ok(quote! {}),
+    };
+
+    let mut setters = vec![];
+    for field in fields {
+        if !is_filterable(field)? {
+            continue;
+        }
+        if !ast.generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                &ast.generics,
+                "`#[filterable]` is not supported on generic resources",
+            ));
+        }
+        let name = field.ident.as_ref().ok_or_else(|| {
+            Error::new_spanned(field, "`#[filterable]` requires a named field")
+        })?;
+        let ty = option_inner_type(&field.ty).unwrap_or(&field.ty);
+        let param = name.to_string();
+        let comment = format!("Only return resources with the specified `{}`.", name);
+        setters.push(quote! {
+            #[doc = #comment]
+            pub fn #name<V: Into<#ty>>(self, value: V) -> Self {
+                let value: #ty = value.into();
+                self.param(#param, value.to_string())
+            }
+        });
+        if is_numeric(ty) {
+            for (suffix, description) in &[
+                ("gt", "greater than"),
+                ("gte", "greater than or equal to"),
+                ("lt", "less than"),
+                ("lte", "less than or equal to"),
+            ] {
+                let method = Ident::new(&format!("{}_{}", name, suffix), name.span());
+                let param = format!("{}__{}", name, suffix);
+                let comment = format!(
+                    "Only return resources with `{}` {} `value`.",
+                    name, description,
+                );
+                setters.push(quote! {
+                    #[doc = #comment]
+                    pub fn #method(self, value: #ty) -> Self {
+                        self.param(#param, value.to_string())
+                    }
+                });
+            }
+        }
+    }
+    if setters.is_empty() {
+        return Ok(quote! {});
+    }
+
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let filter_name = Ident::new(&format!("{}ListFilter", name), name.span());
+    let filter_comment = format!(
+        "Which `{}` resources should `Client::list` return? Unlike `ListFilter`, \
+         this only allows filtering on fields that `{}` actually has.",
+        name, name,
+    );
+    Ok(quote! {
+        #[doc = #filter_comment]
+        #[derive(Clone, Debug, Default)]
+        #vis struct #filter_name {
+            /// The untyped filter we're building.
+            filter: crate::resource::ListFilter,
+        }
+
+        impl #filter_name {
+            /// Create a filter which matches all resources.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Only return resources with the specified tag.
+            pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+                self.filter = self.filter.tag(tag);
+                self
+            }
+
+            /// Only return resources with exactly the specified name.
+            pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+                self.filter = self.filter.name(name);
+                self
+            }
+
+            /// Fetch `page_size` resources per request. This does not limit
+            /// the total number of resources returned.
+            pub fn page_size(mut self, page_size: usize) -> Self {
+                self.filter = self.filter.page_size(page_size);
+                self
+            }
+
+            #( #setters )*
+
+            /// Add a raw query parameter.
+            fn param(mut self, key: &str, value: String) -> Self {
+                self.filter = self.filter.param(key, value);
+                self
+            }
+        }
+
+        impl crate::resource::AsListFilter<#name> for #filter_name {
+            fn as_list_filter(&self) -> &crate::resource::ListFilter {
+                &self.filter
+            }
+        }
+
+        impl From<#filter_name> for crate::resource::ListFilter {
+            fn from(filter: #filter_name) -> Self {
+                filter.filter
+            }
+        }
+    })
+}
+
+/// Is this field marked with `#[filterable]`?
+fn is_filterable(field: &syn::Field) -> Result<bool> {
+    for attr in &field.attrs {
+        if attr.path.is_ident("filterable") {
+            return match attr.parse_meta()? {
+                Meta::Path(_) => Ok(true),
+                other => Err(Error::new_spanned(
+                    other,
+                    "`#[filterable]` does not take arguments",
+                )),
+            };
+        }
+    }
+    Ok(false)
+}
+
+/// Is `ty` a numeric type that BigML can compare?
+fn is_numeric(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .get_ident()
+            .is_some_and(|ident| NUMERIC_TYPES.iter().any(|t| ident == t)),
+        _ => false,
+    }
+}
@@ -2,15 +2,24 @@
 
 // In this macro, we want `proc_macro2::TokenStream` to manipulate the AST using
 // high-level APIs.
-use proc_macro2::TokenStream;
-use syn::{Attribute, DeriveInput, Lit, Meta, MetaNameValue};
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::{
+    Attribute, DeriveInput, Error, Lit, LitStr, Meta, MetaNameValue, NestedMeta,
+    Result,
+};
+
+use crate::list_filter;
 
 /// Do the actual code generation for a `Resource`.
-pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
+pub(crate) fn derive(ast: &DeriveInput) -> Result<TokenStream> {
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-    let api_name = get_api_name(&ast.attrs);
-    quote! {
+    let api_name = get_api_name(&ast.attrs)?;
+    let opts = ResourceOptions::from_attrs(&ast.attrs)?;
+    let id = field_path(opts.id.as_ref(), "resource")?;
+    let status = field_path(opts.status.as_ref(), "status")?;
+    let list_filter = list_filter::derive(ast)?;
+    Ok(quote! {
         impl #impl_generics Resource for #name #ty_generics #where_clause {
             fn id_prefix() -> &'static str {
                 concat!(#api_name, "/")
@@ -20,35 +29,261 @@ pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
                 concat!("/", #api_name)
             }
 
+            fn resource_type() -> ::bigml::resource::ResourceType {
+                ::bigml::resource::ResourceType::from_api_name(#api_name)
+            }
+
             fn common(&self) -> &ResourceCommon {
                 &self.common
             }
 
             fn id(&self) -> &Id<Self> {
-                &self.resource
+                &self.#( #id ).*
             }
 
-            fn status(&self) -> &Status {
-                &self.status
+            fn status(&self) -> &dyn Status {
+                &self.#( #status ).*
             }
         }
+
+        #list_filter
+    })
+}
+
+/// Invoke the `macro_rules!` macro `callback` with `Variant => "api_name",`
+/// for each of our `KNOWN_RESOURCE_TYPES`.
+pub(crate) fn known_resource_types(callback: &Ident) -> TokenStream {
+    let variants = KNOWN_RESOURCE_TYPES
+        .iter()
+        .map(|&(variant, _)| Ident::new(variant, Span::call_site()));
+    let names = known_api_names();
+    quote! {
+        #callback! { #( #variants => #names, )* }
     }
 }
 
 /// Search for an `#[api_name = "my_resource"]` attribute and return
 /// `"my_resource"` as a `Lit` value.
-fn get_api_name(attrs: &[Attribute]) -> Lit {
+fn get_api_name(attrs: &[Attribute]) -> Result<Lit> {
     for attr in attrs {
+        if !attr.path.is_ident("api_name") {
+            continue;
+        }
         // Parse the `#[...]` expression, called a "meta" in Rust's grammar.
-        let meta = attr
-            .parse_meta()
-            .expect("Invalid `api_name`, try #[api_name = \"my_resource\"]");
-        if meta.path().is_ident("api_name") {
-            match meta {
-                Meta::NameValue(MetaNameValue { lit, .. }) => return lit,
-                _ => panic!("Invalid `api_name`, try #[api_name = \"my_resource\"]"),
+        let invalid = || Error::new_spanned(attr, INVALID_API_NAME);
+        match attr.parse_meta().map_err(|_| invalid())? {
+            Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(lit), ..
+            }) => {
+                validate_api_name(&lit.value())
+                    .map_err(|msg| Error::new_spanned(&lit, msg))?;
+                return Ok(Lit::Str(lit));
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Err(Error::new(
+        Span::call_site(),
+        "missing attribute `api_name`, try `#[api_name = \"my_resource\"]`",
+    ))
+}
+
+/// Resource types used by the BigML API, with the name of the matching
+/// `ResourceType` variant. Other names are allowed, because BigML adds new
+/// resource types from time to time, but names which are very similar to one
+/// of these are probably typos.
+const KNOWN_RESOURCE_TYPES: &[(&str, &str)] = &[
+    ("Anomaly", "anomaly"),
+    ("AnomalyScore", "anomalyscore"),
+    ("Association", "association"),
+    ("AssociationSet", "associationset"),
+    ("BatchAnomalyScore", "batchanomalyscore"),
+    ("BatchCentroid", "batchcentroid"),
+    ("BatchPrediction", "batchprediction"),
+    ("BatchProjection", "batchprojection"),
+    ("BatchTopicDistribution", "batchtopicdistribution"),
+    ("Centroid", "centroid"),
+    ("Cluster", "cluster"),
+    ("Configuration", "configuration"),
+    ("Correlation", "correlation"),
+    ("Dataset", "dataset"),
+    ("Deepnet", "deepnet"),
+    ("Ensemble", "ensemble"),
+    ("Evaluation", "evaluation"),
+    ("Execution", "execution"),
+    ("ExternalConnector", "externalconnector"),
+    ("Forecast", "forecast"),
+    ("Fusion", "fusion"),
+    ("Library", "library"),
+    ("LinearRegression", "linearregression"),
+    ("LogisticRegression", "logisticregression"),
+    ("Model", "model"),
+    ("OptiMl", "optiml"),
+    ("Pca", "pca"),
+    ("Prediction", "prediction"),
+    ("Project", "project"),
+    ("Projection", "projection"),
+    ("Sample", "sample"),
+    ("Script", "script"),
+    ("Source", "source"),
+    ("StatisticalTest", "statisticaltest"),
+    ("TimeSeries", "timeseries"),
+    ("TopicDistribution", "topicdistribution"),
+    ("TopicModel", "topicmodel"),
+];
+
+/// The API names in `KNOWN_RESOURCE_TYPES`.
+fn known_api_names() -> impl Iterator<Item = &'static str> {
+    KNOWN_RESOURCE_TYPES.iter().map(|&(_, name)| name)
+}
+
+/// Names which are definitely not BigML resource types, but which are easy to
+/// type by mistake because they're the names of common fields.
+const RESERVED_API_NAMES: &[&str] =
+    &["common", "id", "name", "resource", "status", "tags"];
+
+/// Check that `api_name` looks like a BigML resource type, returning an error
+/// message if it doesn't.
+fn validate_api_name(api_name: &str) -> std::result::Result<(), String> {
+    let is_identifier = api_name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase())
+        && api_name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    if !is_identifier {
+        return Err(format!(
+            "`api_name` should be a lowercase BigML resource type like \"dataset\", not {:?}",
+            api_name,
+        ));
+    }
+    if RESERVED_API_NAMES.contains(&api_name) {
+        return Err(format!(
+            "{:?} is not a BigML resource type, try something like \"dataset\"",
+            api_name,
+        ));
+    }
+    if known_api_names().any(|known| known == api_name) {
+        return Ok(());
+    }
+    if let Some(known) =
+        known_api_names().find(|known| edit_distance(api_name, known) <= 2)
+    {
+        return Err(format!(
+            "unknown `api_name` {:?}, did you mean {:?}?",
+            api_name, known,
+        ));
+    }
+    Ok(())
+}
+
+/// The Levenshtein distance between two ASCII strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            cur.push(substitution.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// The error we report for a malformed `api_name`.
+const INVALID_API_NAME: &str =
+    "invalid `api_name`, try `#[api_name = \"my_resource\"]`";
+
+/// Options specified by a `#[resource(...)]` attribute.
+#[derive(Default)]
+struct ResourceOptions {
+    /// The field containing our ID, if not `resource`.
+    id: Option<LitStr>,
+    /// The field containing our status, if not `status`.
+    status: Option<LitStr>,
+}
+
+impl ResourceOptions {
+    /// Collect options from any `#[resource(...)]` attributes.
+    fn from_attrs(attrs: &[Attribute]) -> Result<ResourceOptions> {
+        let mut opts = ResourceOptions::default();
+        for attr in attrs {
+            if !attr.path.is_ident("resource") {
+                continue;
+            }
+            let options = match attr.parse_meta()? {
+                Meta::List(list) => list.nested,
+                other => {
+                    return Err(Error::new_spanned(
+                        other,
+                        "expected `#[resource(status = \"...\", id = \"...\")]`",
+                    ));
+                }
+            };
+            for option in options {
+                let (slot, lit) = match &option {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(lit),
+                        ..
+                    })) if path.is_ident("id") => (&mut opts.id, lit),
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(lit),
+                        ..
+                    })) if path.is_ident("status") => (&mut opts.status, lit),
+                    _ => {
+                        return Err(Error::new_spanned(
+                            option,
+                            "unexpected option in `#[resource(..)]`",
+                        ));
+                    }
+                };
+                if slot.is_some() {
+                    return Err(Error::new_spanned(
+                        option,
+                        "duplicate option in `#[resource(..)]`",
+                    ));
+                }
+                *slot = Some(lit.clone());
             }
         }
+        Ok(opts)
     }
-    panic!("Missing attribute `api_name`, try `#[api_name = \"...\"]`");
+}
+
+/// Parse a field path like `"status"` or `"execution.status"` into a list of
+/// identifiers, falling back to `default` if no path was specified.
+fn field_path(path: Option<&LitStr>, default: &str) -> Result<Vec<Ident>> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(vec![Ident::new(default, Span::call_site())]),
+    };
+    path.value()
+        .split('.')
+        .map(|part| {
+            let mut ident = syn::parse_str::<Ident>(part).map_err(|_| {
+                Error::new_spanned(path, "expected a field name like \"status\"")
+            })?;
+            // Point any errors about missing fields at the attribute.
+            ident.set_span(path.span());
+            Ok(ident)
+        })
+        .collect()
+}
+
+#[test]
+fn api_names_are_validated() {
+    assert!(validate_api_name("dataset").is_ok());
+    assert!(validate_api_name("exampleresource").is_ok());
+    assert!(validate_api_name("Dataset").is_err());
+    assert!(validate_api_name("common").is_err());
+    assert!(validate_api_name("batch-prediction").is_err());
+    assert_eq!(
+        validate_api_name("datset").unwrap_err(),
+        "unknown `api_name` \"datset\", did you mean \"dataset\"?",
+    );
 }
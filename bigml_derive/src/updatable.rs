@@ -3,16 +3,24 @@
 // In this macro, we want `proc_macro2::TokenStream` to manipulate the AST using
 // high-level APIs.
 use proc_macro2::{Ident, Span, TokenStream};
-use syn::{Data, DeriveInput, Field, Meta, MetaList, NestedMeta};
+use syn::{
+    Data, DataEnum, DeriveInput, Error, Field, Fields, Meta, MetaList, NestedMeta,
+    Result,
+};
 
 /// Do the actual code generation for a `Resource`.
-pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
+pub(crate) fn derive(ast: &DeriveInput) -> Result<TokenStream> {
+    if let Data::Enum(ref data_enum) = ast.data {
+        return derive_for_enum(ast, data_enum);
+    }
+
     let name = &ast.ident;
     let vis = &ast.vis;
     let update_name = Ident::new(&format!("{}Update", name), Span::call_site());
     let update_comment = format!("An update to `{}`.", name);
-    let update_fields = fields_for_update_type(ast);
-    quote! {
+    let new_comment = format!("Create an empty `{}`.", update_name);
+    let (update_fields, setters) = fields_for_update_type(ast)?;
+    Ok(quote! {
         impl Updatable for #name {
             type Update = #update_name;
         }
@@ -31,23 +39,57 @@ pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
             #[doc(hidden)]
             #vis _placeholder: (),
         }
+
+        impl #update_name {
+            #[doc = #new_comment]
+            #vis fn new() -> Self {
+                Self::default()
+            }
+
+            #( #setters )*
+        }
+    })
+}
+
+/// Simple enums are always updated as a whole, so they use `Self` as their
+/// update type.
+fn derive_for_enum(ast: &DeriveInput, data_enum: &DataEnum) -> Result<TokenStream> {
+    for variant in &data_enum.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "`#[derive(Updatable)]` only supports enums without fields",
+            ));
+        }
     }
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics Updatable for #name #ty_generics #where_clause {
+            type Update = Self;
+        }
+    })
 }
 
 /// Find all `#[updatable]` fields in the original struct, and return a list of
-/// fields for our `*Update` struct.
-fn fields_for_update_type(ast: &DeriveInput) -> Vec<TokenStream> {
+/// fields for our `*Update` struct, plus a chainable setter for each field.
+fn fields_for_update_type(
+    ast: &DeriveInput,
+) -> Result<(Vec<TokenStream>, Vec<TokenStream>)> {
     let mut new_fields = vec![];
+    let mut setters = vec![];
 
     if let Data::Struct(ref data_struct) = ast.data {
         for field in &data_struct.fields {
-            if let Some(field_opts) = updatable_field_options(field) {
+            if let Some(field_opts) = updatable_field_options(field)? {
                 let attrs = &field_opts.attrs;
                 let vis = &field.vis;
-                let name = field
-                    .ident
-                    .as_ref()
-                    .expect("Cannot `#[derive(Updatable)]` for tuple struct");
+                let name = field.ident.as_ref().ok_or_else(|| {
+                    Error::new_spanned(
+                        field,
+                        "cannot `#[derive(Updatable)]` for tuple struct",
+                    )
+                })?;
                 let ty = &field.ty;
                 let comment = format!("New value for `{}` (optional).", name);
                 new_fields.push(quote! {
@@ -55,13 +97,27 @@ fn fields_for_update_type(ast: &DeriveInput) -> Vec<TokenStream> {
                     #( #attrs )*
                     #vis #name: Option<<#ty as Updatable>::Update>,
                 });
+                let setter_comment = format!("Set a new value for `{}`.", name);
+                setters.push(quote! {
+                    #[doc = #setter_comment]
+                    #vis fn #name<V>(mut self, value: V) -> Self
+                    where
+                        V: Into<<#ty as Updatable>::Update>,
+                    {
+                        self.#name = Some(value.into());
+                        self
+                    }
+                });
             }
         }
     } else {
-        panic!("`#[derive(Updatable)]` may only be used on structs");
+        return Err(Error::new_spanned(
+            &ast.ident,
+            "`#[derive(Updatable)]` may only be used on structs and enums",
+        ));
     }
 
-    new_fields
+    Ok((new_fields, setters))
 }
 
 /// Options specified by an `#[updatable(...)]` attribute.
@@ -73,68 +129,80 @@ struct UpdatableFieldOptions {
 
 /// If the specified structure field is marked with `#[updatable]` or
 /// `#[updatable(..)]`, return all relevant information.
-fn updatable_field_options(field: &Field) -> Option<UpdatableFieldOptions> {
+fn updatable_field_options(field: &Field) -> Result<Option<UpdatableFieldOptions>> {
     let mut updatable = false;
     let mut field_opts = UpdatableFieldOptions::default();
     let mut flatten = false;
     for attr in &field.attrs {
-        let meta = attr.parse_meta().expect("unparseable attribute");
-        if meta.path().is_ident("updatable") {
-            updatable = true;
-            match meta {
-                // We have `#[updatable]`, do nothing.
-                Meta::Path(_) => {}
-                // We have `#[updatable(..)]`, look for nested options.
-                Meta::List(MetaList {
-                    nested: options, ..
-                }) => {
-                    for option in options {
-                        match option {
-                            // We have a `flatten` option.
-                            NestedMeta::Meta(ref flatten_meta)
-                                if flatten_meta.path().is_ident("flatten") =>
-                            {
-                                if let Meta::Path(_) = flatten_meta {
-                                    flatten = true;
-                                } else {
-                                    panic!(
-                                        "#[updatable(flatten)] may not have arguments"
-                                    );
-                                }
+        if !attr.path.is_ident("updatable") {
+            continue;
+        }
+        updatable = true;
+        match attr.parse_meta()? {
+            // We have `#[updatable]`, do nothing.
+            Meta::Path(_) => {}
+            // We have `#[updatable(..)]`, look for nested options.
+            Meta::List(MetaList {
+                nested: options, ..
+            }) => {
+                for option in options {
+                    match option {
+                        // We have a `flatten` option.
+                        NestedMeta::Meta(ref flatten_meta)
+                            if flatten_meta.path().is_ident("flatten") =>
+                        {
+                            if let Meta::Path(_) = flatten_meta {
+                                flatten = true;
+                            } else {
+                                return Err(Error::new_spanned(
+                                    flatten_meta,
+                                    "#[updatable(flatten)] may not have arguments",
+                                ));
                             }
+                        }
 
-                            // We have an `attr(..)` option, so extract it and
-                            // add to `field_opts.attrs`.
-                            //
-                            // TODO: Do we want to keep this? It's not being used, but it's
-                            // potentially quite useful.
-                            NestedMeta::Meta(ref attr_meta)
-                                if attr_meta.path().is_ident("attr") =>
-                            {
-                                match attr_meta {
-                                    Meta::List(MetaList {
-                                        nested: attr_values,
-                                        ..
-                                    }) => {
-                                        for attr_value in attr_values {
-                                            // Wrap in `#[..]`.
-                                            field_opts.attrs.push(quote! {
-                                                #[ #attr_value ]
-                                            });
-                                        }
-                                    }
-                                    _ => {
-                                        panic!("cannot parse `#[updatable(attr(..))]`")
+                        // We have an `attr(..)` option, so extract it and
+                        // add to `field_opts.attrs`.
+                        //
+                        // TODO: Do we want to keep this? It's not being used, but it's
+                        // potentially quite useful.
+                        NestedMeta::Meta(ref attr_meta)
+                            if attr_meta.path().is_ident("attr") =>
+                        {
+                            match attr_meta {
+                                Meta::List(MetaList {
+                                    nested: attr_values,
+                                    ..
+                                }) => {
+                                    for attr_value in attr_values {
+                                        // Wrap in `#[..]`.
+                                        field_opts.attrs.push(quote! {
+                                            #[ #attr_value ]
+                                        });
                                     }
                                 }
-                            }
-                            _ => {
-                                panic!("unexpected option in `#[updatable(..)]`");
+                                _ => {
+                                    return Err(Error::new_spanned(
+                                        attr_meta,
+                                        "cannot parse `#[updatable(attr(..))]`",
+                                    ));
+                                }
                             }
                         }
+                        other => {
+                            return Err(Error::new_spanned(
+                                other,
+                                "unexpected option in `#[updatable(..)]`",
+                            ));
+                        }
                     }
                 }
-                _ => panic!("expected `#[updatable]` or `#[updatable(..)]`"),
+            }
+            other => {
+                return Err(Error::new_spanned(
+                    other,
+                    "expected `#[updatable]` or `#[updatable(..)]`",
+                ));
             }
         }
     }
@@ -146,8 +214,8 @@ fn updatable_field_options(field: &Field) -> Option<UpdatableFieldOptions> {
         });
     }
     if updatable {
-        Some(field_opts)
+        Ok(Some(field_opts))
     } else {
-        None
+        Ok(None)
     }
 }